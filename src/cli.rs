@@ -3,14 +3,128 @@
 
 use cu::pre::*;
 
-use crate::Config;
+use crate::{
+    Config, Failure, FileFailure, Holders, Issue, IssueKind, NoticeOptions, OutputFormat,
+    RunOptions,
+};
+
+/// Exit code when issues are found (or not fixed) in the files
+pub const EXIT_ISSUES: u8 = 1;
+/// Exit code when the tool itself fails, such as when the config can't be read
+pub const EXIT_ERROR: u8 = 2;
 
 /// Check or fix license notices
 #[derive(Debug, Clone, PartialEq, clap::Parser)]
+#[clap(
+    after_help = "Exit codes: 0 if successful, 1 if issues are found (or not fixed), 2 if the tool itself fails (such as failing to read the config)"
+)]
 pub struct Cli {
     /// Attempt fix the license notice on the files
     #[clap(short, long)]
     pub fix: bool,
+    /// Only list the files matched by each glob in the config, with the holder
+    /// and license for them. No file is read or modified, even with --fix
+    #[clap(long)]
+    pub list: bool,
+    /// Only validate the config and print each glob with the holder and license for it.
+    /// No source file is read or modified
+    #[clap(long, conflicts_with_all(["fix", "list", "report_uncovered", "auto_extend"]))]
+    pub check_config: bool,
+    /// Print a diff of what --fix would change for each file with issues,
+    /// without modifying any file
+    #[clap(long, conflicts_with_all(["fix", "remove"]))]
+    pub diff: bool,
+    /// Remove the license notices from the files, instead of checking or fixing them.
+    /// Notices after a sentinel line are kept
+    #[clap(long)]
+    pub remove: bool,
+    /// In fix mode, only fix files matching this glob. Other files are only checked
+    #[clap(long, requires("fix"))]
+    pub only_path: Option<String>,
+    /// Compare the copyright holder case-insensitively when checking.
+    /// Fixing will still change the holder to the configured casing
+    #[clap(long)]
+    pub ignore_case_holder: bool,
+    /// Accept copyright years ending before the current year when checking.
+    /// Fixing will still update them
+    #[clap(long)]
+    pub allow_stale_year: bool,
+    /// Use YYYY as the current year when checking and fixing, instead of the
+    /// system clock (or SOURCE_DATE_EPOCH, if set), for reproducible output
+    #[clap(long, value_name = "YYYY")]
+    pub year: Option<u32>,
+    /// In fix mode, abort without modifying any file if more than N files would be changed
+    #[clap(long, value_name = "N", requires("fix"))]
+    pub max_changes: Option<usize>,
+    /// In fix mode, only fix files where the copyright year is the only issue
+    #[clap(long, requires("fix"))]
+    pub year_only: bool,
+    /// In fix mode, only report the files that would be fixed, without modifying any file
+    #[clap(long, requires("fix"))]
+    pub dry_run: bool,
+    /// Stop at the first file with issues, without checking the other files
+    #[clap(long)]
+    pub fail_fast: bool,
+    /// Only print the final counts instead of each file with issues,
+    /// along with the first N files with issues if specified
+    #[clap(long, value_name = "N", num_args = 0..=1, default_missing_value = "0")]
+    pub summary: Option<usize>,
+    /// Format of the output. `json` prints the result of each file as a JSON array
+    #[clap(long = "format", value_name = "FORMAT", default_value = "text", value_parser = ["text", "json"])]
+    pub output_format: String,
+    /// In fix mode, use the year the file was added in git as the start year for new notices
+    #[clap(long, requires("fix"))]
+    pub git_year: bool,
+    /// Skip files ignored by git (.gitignore, .git/info/exclude) in the current repo
+    #[clap(long)]
+    pub respect_gitignore: bool,
+    /// In fix mode, do not modify files with uncommitted changes in git
+    #[clap(long, requires("fix"))]
+    pub no_fix_if_uncommitted: bool,
+    /// Maximum number of files processed at the same time. Defaults to 1024
+    #[clap(long, value_name = "N")]
+    pub jobs: Option<usize>,
+    /// Limit the total size (in MiB) of files being processed at the same time.
+    /// Defaults to 256 MiB if specified without a value
+    #[clap(long, value_name = "MIB", num_args = 0..=1, default_missing_value = "256")]
+    pub memory_budget: Option<u64>,
+    /// Only check (or fix) the files staged in git, for pre-commit hooks.
+    /// Fixed files are staged again, and files with unstaged changes are not fixed
+    #[clap(long)]
+    pub staged: bool,
+    /// Skip files larger than this size (in KiB). Binary files are always skipped
+    #[clap(long, value_name = "KIB")]
+    pub max_file_size: Option<u64>,
+    /// Instead of checking, report files under this directory that are not
+    /// covered by any glob in the config
+    #[clap(long, value_name = "ROOT", conflicts_with("fix"))]
+    pub report_uncovered: Option<String>,
+    /// With --report-uncovered, only report files with these extensions (comma-separated)
+    #[clap(
+        long,
+        value_name = "EXT",
+        value_delimiter = ',',
+        requires("report_uncovered")
+    )]
+    pub extensions: Vec<String>,
+    /// Propose config entries for file extensions not covered by the config,
+    /// and add them to the config file after confirmation
+    #[clap(long, conflicts_with_all(["holder", "report_uncovered"]))]
+    pub auto_extend: bool,
+    /// Add the entries proposed by --auto-extend without confirmation
+    #[clap(long, requires("auto_extend"))]
+    pub yes: bool,
+    /// Read the content of a single file from stdin, and write the fixed content
+    /// (or the original content, when checking) to stdout. Requires --path, --holder and --license
+    #[clap(
+        long,
+        requires_all(["stdin_path", "holder"]),
+        conflicts_with_all(["paths", "report_uncovered", "auto_extend"])
+    )]
+    pub stdin: bool,
+    /// In stdin mode, the path of the file, used to determine the format
+    #[clap(long = "path", value_name = "PATH", requires("stdin"))]
+    pub stdin_path: Option<String>,
     /// In inline config mode, specify the copyright holder
     #[clap(short = 'H', long, requires("license"))]
     pub holder: Option<String>,
@@ -18,49 +132,407 @@ pub struct Cli {
     #[clap(short = 'L', long, requires("holder"))]
     pub license: Option<String>,
 
+    /// Fetch the config from a shared policy URL. If the policy cannot be fetched,
+    /// the cached policy or the local config is used instead
+    #[cfg(feature = "policy")]
+    #[clap(long)]
+    pub policy: Option<String>,
+
     #[clap(flatten)]
     pub common: cu::cli::Flags,
 
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+
     /// Paths to config files, or in inline config mode, glob patterns for source files
     /// to apply the license notice.
     pub paths: Vec<String>,
 }
 
+/// Subcommands for tasks other than checking or fixing
+#[derive(Debug, Clone, PartialEq, clap::Subcommand)]
+pub enum Command {
+    /// Print suggested config entries for files not covered by the config in
+    /// the current directory
+    Suggest {
+        /// Directory to search for files
+        #[clap(default_value = ".")]
+        root: String,
+        /// Copyright holder for the suggested entries
+        #[clap(short = 'H', long, default_value = "HOLDER")]
+        holder: String,
+        /// SPDX ID for the suggested entries
+        #[clap(short = 'L', long, default_value = "LICENSE")]
+        license: String,
+    },
+    /// Print the shell completion script
+    Completions {
+        /// The shell to generate the completion script for
+        shell: clap_complete::Shell,
+    },
+}
+
+/// Execute a subcommand
+pub fn run_command(command: Command) -> cu::Result<()> {
+    match command {
+        Command::Suggest {
+            root,
+            holder,
+            license,
+        } => {
+            let config = match crate::try_find_default_config_file() {
+                Some(config_path) => Some(Config::build(config_path)?),
+                None => None,
+            };
+            let globs = crate::suggest_globs(std::path::Path::new(&root), config.as_ref())?;
+            if globs.is_empty() {
+                cu::info!("all files are covered by the config.");
+                return Ok(());
+            }
+            print!("{}", crate::suggest_config(&globs, &holder, &license)?);
+            Ok(())
+        }
+        Command::Completions { shell } => {
+            generate_completions(shell, &mut std::io::stdout());
+            Ok(())
+        }
+    }
+}
+
+/// Write the completion script for `shell` to `out`
+pub fn generate_completions(shell: clap_complete::Shell, out: &mut dyn std::io::Write) {
+    let mut command = <Cli as clap::CommandFactory>::command();
+    clap_complete::generate(shell, &mut command, "lisensor", out);
+}
+
+/// Print each glob in the (already validated) config with the holder and license for it
+pub fn check_config(config: &Config, out: &mut dyn std::io::Write) -> cu::Result<()> {
+    let mut count = 0;
+    for (glob, holder, license) in config.clone().into_iter() {
+        writeln!(out, "{glob} (holder {holder}, license '{license}')")?;
+        count += 1;
+    }
+    cu::info!("config is valid, with {count} glob(s).");
+    Ok(())
+}
+
+/// Report files under `root` not covered by the config, error if there are any.
+/// If `extensions` is not empty, only files with those extensions are reported
+pub fn report_uncovered(
+    root: &str,
+    config: &Config,
+    extensions: &[String],
+) -> cu::Result<Result<(), Failure>> {
+    let uncovered = crate::uncovered_files_with_extensions(
+        std::path::Path::new(root),
+        Some(config),
+        extensions,
+    )?;
+    if uncovered.is_empty() {
+        cu::info!("all files under '{root}' are covered by the config.");
+        return Ok(Ok(()));
+    }
+    for path in &uncovered {
+        cu::warn!("'{}' is not covered by the config", path.display());
+    }
+    cu::error!(
+        "found {} file(s) not covered by the config.",
+        uncovered.len()
+    );
+    let failures = uncovered
+        .into_iter()
+        .map(|path| FileFailure {
+            path,
+            kind: IssueKind::Missing,
+            message: "file is not covered by the config".to_string(),
+            fixable: false,
+        })
+        .collect();
+    Ok(Err(Failure { failures }))
+}
+
+/// Get the path of the config file to be extended by --auto-extend. If multiple
+/// config files are specified, the first one is used
+pub fn auto_extend_path(args: &crate::Cli) -> cu::Result<String> {
+    if let Some(path) = args.paths.first() {
+        return Ok(path.clone());
+    }
+    let path = crate::try_find_default_config_file();
+    let path = cu::check!(path, "--auto-extend requires a config file")?;
+    Ok(path.to_string())
+}
+
+/// Propose entries for file extensions not covered by the config, using the most
+/// common holder and license in the config. After confirmation (or if `yes`),
+/// the entries are added to the config file at `config_path` and to `config`.
+pub fn auto_extend(config_path: &str, config: &mut Config, yes: bool) -> cu::Result<()> {
+    let parent = std::path::Path::new(config_path)
+        .parent()
+        .context("failed to get parent path for config")?;
+    // globs in the config are relative to the config file
+    let root = if parent.as_os_str().is_empty() {
+        std::path::Path::new(".")
+    } else {
+        parent
+    };
+    let globs = crate::suggest_new_extension_globs(root, config)?;
+    if globs.is_empty() {
+        return Ok(());
+    }
+    let Some((holder, license)) = config.most_common_entry() else {
+        cu::warn!("cannot propose entries for new extensions, since '{config_path}' is empty");
+        return Ok(());
+    };
+    if !config_path.ends_with(".toml") {
+        cu::bail!("--auto-extend only supports TOML config files, but got '{config_path}'");
+    }
+    let (holder, license) = (holder.to_string(), license.to_string());
+    let table = crate::suggest_config(&globs, &holder, &license)?;
+    cu::info!("found files with extensions not covered by '{config_path}', proposed entries:");
+    print!("{table}");
+    if !yes && !confirm(&format!("add these entries to '{config_path}'?"))? {
+        cu::info!("'{config_path}' is not changed.");
+        return Ok(());
+    }
+    let content = cu::fs::read_string(config_path)?;
+    let content = crate::extend_config(&content, &globs, &holder, &license)?;
+    cu::fs::write(config_path, content)?;
+    config.absorb(Config::parse(&table, parent, config_path)?)?;
+    cu::info!("added {} entries to '{config_path}'.", globs.len());
+    Ok(())
+}
+
+/// Ask the user to confirm on the terminal
+fn confirm(prompt: &str) -> cu::Result<bool> {
+    use std::io::Write as _;
+    print!("{prompt} [y/N] ");
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+/// Check or fix the content read from `input` as if it's the file at --path,
+/// and write the fixed content (or the original content, if not fixing) to `output`.
+/// The issue is printed to stderr, since logs would mix with the content on stdout.
+///
+/// Errors if the content has issues that are not fixed
+pub fn run_stdin(
+    args: &mut crate::Cli,
+    input: &mut dyn std::io::Read,
+    output: &mut dyn std::io::Write,
+) -> cu::Result<Result<(), Failure>> {
+    // clap ensures these are present
+    let path = cu::check!(args.stdin_path.take(), "--stdin requires --path")?;
+    let holder = cu::check!(args.holder.take(), "--stdin requires --holder")?;
+    let holder = Holders::from(holder);
+    let license = cu::check!(args.license.take(), "--stdin requires --license")?;
+    let options = NoticeOptions {
+        ignore_case_holder: args.ignore_case_holder,
+        allow_stale_year: args.allow_stale_year,
+        year: args.year,
+        ..Default::default()
+    };
+    let mut content = String::new();
+    input.read_to_string(&mut content)?;
+    let first_line = content.lines().next().unwrap_or_default();
+    let format = options.format_for_content(std::path::Path::new(&path), first_line);
+
+    let check_options = if args.fix {
+        options.strict()
+    } else {
+        options.clone()
+    };
+    let Err(e) = format.check_content(&content, &holder, &license, &check_options) else {
+        output.write_all(content.as_bytes())?;
+        return Ok(Ok(()));
+    };
+    if !args.fix {
+        output.write_all(content.as_bytes())?;
+        eprintln!("'{path}': {e}");
+        return Ok(Err(stdin_failure(path, &e)));
+    }
+    match format.fix_content(&content, &holder, &license, &options) {
+        Ok(fixed) => {
+            output.write_all(fixed.as_bytes())?;
+            Ok(Ok(()))
+        }
+        Err(e) => {
+            output.write_all(content.as_bytes())?;
+            eprintln!("failed to fix '{path}': {e}");
+            Ok(Err(stdin_failure(path, &e)))
+        }
+    }
+}
+
+/// Create the failure for the content read from stdin
+fn stdin_failure(path: String, e: &cu::Error) -> Failure {
+    let issue = Issue::from_error(e);
+    let failure = FileFailure {
+        path: path.into(),
+        kind: issue.kind,
+        message: issue.message().to_string(),
+        fixable: false,
+    };
+    Failure {
+        failures: vec![failure],
+    }
+}
+
 /// Convert the CLI args into configuration object
 pub fn config_from_cli(args: &mut crate::Cli) -> cu::Result<Config> {
-    match (args.holder.take(), args.license.take()) {
-        (Some(holder), Some(license)) => {
-            if let Some(config_path) = crate::try_find_default_config_file() {
-                cu::bail!(
-                    "--holder or --license cannot be specified when {config_path} is present in the current directory"
-                );
-            }
-            Ok(Config::new(
-                holder,
-                license,
-                std::mem::take(&mut args.paths),
-            ))
+    check_mode_args(args)?;
+    let mut config = config_from_cli_args(args)?;
+    apply_notice_options(args, &mut config);
+    Ok(config)
+}
+
+/// Check the flags that decide what to do with the files can be used together
+fn check_mode_args(args: &crate::Cli) -> cu::Result<()> {
+    if args.remove && args.fix {
+        cu::bail!("--remove and --fix cannot be used together");
+    }
+    Ok(())
+}
+
+/// Apply the options for checking and fixing notices specified on the command line
+fn apply_notice_options(args: &crate::Cli, config: &mut Config) {
+    let options = config.notice_options_mut();
+    if args.ignore_case_holder {
+        options.ignore_case_holder = true;
+    }
+    if args.allow_stale_year {
+        options.allow_stale_year = true;
+    }
+    if args.year.is_some() {
+        options.year = args.year;
+    }
+}
+
+/// Environment variable for the holder in inline config mode, if --holder is not specified
+pub const HOLDER_ENV: &str = "LISENSOR_HOLDER";
+/// Environment variable for the license in inline config mode, if --license is not specified
+pub const LICENSE_ENV: &str = "LISENSOR_LICENSE";
+
+/// Get the holder and license from [`HOLDER_ENV`] and [`LICENSE_ENV`], if both are set
+fn inline_config_from_env() -> cu::Result<Option<(String, String)>> {
+    let get = |name| std::env::var(name).ok().filter(|x| !x.is_empty());
+    match (get(HOLDER_ENV), get(LICENSE_ENV)) {
+        (Some(holder), Some(license)) => Ok(Some((holder, license))),
+        (None, None) => Ok(None),
+        (Some(_), None) => cu::bail!("{HOLDER_ENV} is set, but {LICENSE_ENV} is not"),
+        (None, Some(_)) => cu::bail!("{LICENSE_ENV} is set, but {HOLDER_ENV} is not"),
+    }
+}
+
+/// Error if one of the globs looks like a config file. With the inline config from
+/// the environment variables, config files on the command line are used as globs,
+/// which is easy to miss since the environment variables are not on the command line
+fn check_no_config_file_globs(globs: &[String]) -> cu::Result<()> {
+    for glob in globs {
+        if glob.contains(['*', '?', '[', '{']) {
+            continue;
+        }
+        let extension = std::path::Path::new(glob)
+            .extension()
+            .and_then(|x| x.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        if matches!(extension.as_str(), "toml" | "json" | "yaml" | "yml") {
+            cu::bail!(
+                "'{glob}' looks like a config file, but it's used as a glob since {HOLDER_ENV} and {LICENSE_ENV} are set. Unset them to use the config file, or specify --holder and --license to check it as a file."
+            );
         }
+    }
+    Ok(())
+}
+
+fn config_from_cli_args(args: &mut crate::Cli) -> cu::Result<Config> {
+    // --holder and --license take precedence over the environment variables,
+    // which take precedence over the config file
+    let inline = match (args.holder.take(), args.license.take()) {
+        (Some(holder), Some(license)) => Some((holder, license, "--holder or --license")),
         // clap ensures both are None
-        _ => {
-            let mut iter = std::mem::take(&mut args.paths).into_iter();
-            let mut config = match iter.next() {
-                None => {
-                    let Some(config_path) = crate::try_find_default_config_file() else {
-                        cu::bail!(
-                            "cannot find Lisensor.toml, and no config files are specified on the command line."
-                        );
-                    };
-                    Config::build(config_path)?
-                }
-                Some(first) => Config::build(&first)?,
+        _ => match inline_config_from_env()? {
+            Some((holder, license)) => {
+                check_no_config_file_globs(&args.paths)?;
+                Some((holder, license, "LISENSOR_HOLDER or LISENSOR_LICENSE"))
+            }
+            None => None,
+        },
+    };
+    if let Some((holder, license, source)) = inline {
+        if let Some(config_path) = crate::try_find_default_config_file() {
+            cu::bail!(
+                "{source} cannot be specified when {config_path} is present in the current directory"
+            );
+        }
+        return Ok(Config::new(
+            holder,
+            license,
+            std::mem::take(&mut args.paths),
+        ));
+    }
+    let mut iter = std::mem::take(&mut args.paths).into_iter();
+    let mut config = match iter.next() {
+        None => {
+            let Some(config_path) = crate::try_find_default_config_file() else {
+                cu::bail!(
+                    "cannot find Lisensor.toml, and no config files are specified on the command line. To use inline config, specify --holder and --license, or set {HOLDER_ENV} and {LICENSE_ENV}."
+                );
             };
+            Config::build(config_path)?
+        }
+        Some(first) => Config::build(&first)?,
+    };
 
-            for path in iter {
-                config.absorb(Config::build(&path)?)?;
-            }
+    for path in iter {
+        config.absorb(Config::build(&path)?)?;
+    }
 
-            Ok(config)
-        }
+    Ok(config)
+}
+
+/// Convert the CLI args into options for running the tool
+pub fn options_from_cli(args: &mut crate::Cli) -> RunOptions {
+    RunOptions {
+        fix: args.fix,
+        only_path: args.only_path.take(),
+        memory_budget: args
+            .memory_budget
+            .map(|mib| mib.saturating_mul(1024 * 1024)),
+        no_fix_if_uncommitted: args.no_fix_if_uncommitted,
+        max_changes: args.max_changes,
+        year_only: args.year_only,
+        respect_gitignore: args.respect_gitignore,
+        remove: args.remove,
+        diff: args.diff,
+        list: args.list,
+        git_year: args.git_year,
+        jobs: args.jobs,
+        max_file_size: args.max_file_size.map(|kib| kib.saturating_mul(1024)),
+        staged: args.staged,
+        summary: args.summary,
+        fail_fast: args.fail_fast,
+        dry_run: args.dry_run,
+        output_format: match args.output_format.as_str() {
+            "json" => OutputFormat::Json,
+            _ => OutputFormat::Text,
+        },
+    }
+}
+
+/// Get the config from the shared policy at `url`, falling back to
+/// the config from the CLI args if the policy is not available
+#[cfg(feature = "policy")]
+pub async fn config_from_policy(url: &str, args: &mut crate::Cli) -> cu::Result<Config> {
+    check_mode_args(args)?;
+    let cache_path = std::path::Path::new(crate::POLICY_CACHE_FILE);
+    // globs in the policy are relative to the current directory
+    if let Some(mut config) = crate::fetch_policy(url, std::path::Path::new(""), cache_path).await {
+        apply_notice_options(args, &mut config);
+        return Ok(config);
     }
+    cu::warn!("policy from '{url}' is not available, falling back to local config");
+    config_from_cli(args)
 }
@@ -1,39 +1,153 @@
 // SPDX-License-Identifier: MIT
 // Copyright (c) 2025-2026 Pistonite
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::ops::Deref;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use cu::pre::*;
 
+use crate::{
+    CopyrightStyle, Declarations, DiskFileSystem, FileSystem, Format, Holders, NoticeOptions,
+};
+
 /// Try finding the default config files according to the order
 /// specified in the documentation (see repo README)
 pub fn try_find_default_config_file() -> Option<&'static str> {
-    ["Lisensor.toml", "lisensor.toml"]
-        .into_iter()
-        .find(|x| Path::new(x).exists())
+    [
+        "Lisensor.toml",
+        "lisensor.toml",
+        "lisensor.json",
+        "lisensor.yaml",
+        "lisensor.yml",
+    ]
+    .into_iter()
+    .find(|x| Path::new(x).exists())
 }
 
 /// Config object
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct Config {
     // glob -> (holder, license)
-    globs: BTreeMap<String, (Arc<String>, Arc<String>)>,
+    globs: BTreeMap<String, (Arc<Holders>, Arc<String>)>,
+    // files matching these globs are skipped
+    excludes: Vec<String>,
+    // keys in `globs` that are file paths, not expanded as globs
+    literal_paths: BTreeSet<String>,
+    options: NoticeOptions,
 }
 
-/// Raw config read from a toml config file.
+/// Raw config read from a config file (TOML, JSON or YAML).
 ///
-/// The format is holder -> glob -> license
+/// Other than the reserved option keys,
+/// the format is holder -> glob -> license
+#[derive(Deserialize)]
+struct RawConfig {
+    /// See [`NoticeOptions::normalize_typography`]
+    #[serde(default)]
+    normalize_typography: bool,
+    /// Extension -> format name, see [`NoticeOptions::formats`]
+    #[serde(default)]
+    format: BTreeMap<String, String>,
+    /// See [`NoticeOptions::blank_after`]
+    #[serde(default)]
+    blank_after: BTreeMap<String, bool>,
+    /// See [`NoticeOptions::min_year`]
+    #[serde(default)]
+    min_year: Option<u32>,
+    /// See [`NoticeOptions::max_blank_lines_after`]
+    #[serde(default)]
+    max_blank_lines_after: Option<usize>,
+    /// See [`NoticeOptions::tags_before_license`]
+    #[serde(default)]
+    tags_before_license: Vec<String>,
+    /// See [`NoticeOptions::tags_after_license`]
+    #[serde(default)]
+    tags_after_license: Vec<String>,
+    /// See [`NoticeOptions::max_scan_lines`]
+    #[serde(default)]
+    max_scan_lines: Option<usize>,
+    /// See [`NoticeOptions::max_scan_bytes`]
+    #[serde(default)]
+    max_scan_bytes: Option<u64>,
+    /// See [`NoticeOptions::write_present`]
+    #[serde(default)]
+    write_present: bool,
+    /// See [`NoticeOptions::allow_stale_year`]
+    #[serde(default)]
+    allow_stale_year: bool,
+    /// See [`NoticeOptions::preamble`]
+    #[serde(default)]
+    preamble: Vec<String>,
+    /// Style name, see [`NoticeOptions::copyright_style`]
+    #[serde(default)]
+    copyright_style: Option<String>,
+    /// Globs for files to skip, see [`Config::with_excludes`]
+    #[serde(default)]
+    exclude: Vec<String>,
+    /// Extension -> declarations, see [`NoticeOptions::declarations`]
+    #[serde(default)]
+    declarations: BTreeMap<String, RawDeclarations>,
+    /// Shorthand license name -> canonical SPDX license, for the licenses in this config
+    #[serde(default)]
+    aliases: BTreeMap<String, String>,
+    /// Glob -> holders and license, for files with multiple copyright lines
+    #[serde(default)]
+    multi_holder: BTreeMap<String, MultiHolder>,
+    /// Paths to base configs (relative to this config) that this config extends
+    #[serde(default)]
+    extends: Option<Extends>,
+
+    #[serde(flatten)]
+    holders: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+/// Value in the `multi_holder` table, for files with a copyright line for each holder
+#[derive(Deserialize)]
+struct MultiHolder {
+    holders: Vec<String>,
+    license: String,
+}
+
+/// Value in the `declarations` table
 #[derive(Deserialize)]
-struct TomlConfig(BTreeMap<String, BTreeMap<String, String>>);
+struct RawDeclarations {
+    prefixes: Vec<String>,
+    /// Defaults to 1
+    #[serde(default)]
+    max_lines: Option<usize>,
+}
+
+/// Value of the `extends` key, either one path or a list of paths
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Extends {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Extends {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            Self::One(path) => vec![path],
+            Self::Many(paths) => paths,
+        }
+    }
+}
 
 impl Config {
     /// Create a config object from a single holder and license,
     /// with multiple glob patterns.
     pub fn new(holder: String, license: String, glob_list: Vec<String>) -> Self {
-        let holder = Arc::new(holder);
+        Self::from_holders(Holders::from(holder), license, glob_list)
+    }
+
+    /// Create a config object from multiple holders and a license,
+    /// with multiple glob patterns. Files matched have a copyright line
+    /// for each holder, in order
+    pub fn from_holders(holders: Holders, license: String, glob_list: Vec<String>) -> Self {
+        let holder = Arc::new(holders);
         let license = Arc::new(license);
         let mut globs = BTreeMap::new();
         for glob in glob_list {
@@ -48,75 +162,349 @@ impl Config {
                 }
             }
         }
-        Self { globs }
+        Self {
+            globs,
+            excludes: Vec::new(),
+            literal_paths: BTreeSet::new(),
+            options: NoticeOptions::default(),
+        }
+    }
+
+    /// Create a config object from a single holder and license, with
+    /// paths of files that are checked as is, without expanding them as globs.
+    ///
+    /// This is useful if the files are already discovered by the caller.
+    /// Checking errors if a path is not a file. Errors if a path is not UTF-8
+    pub fn from_paths(holder: String, license: String, paths: Vec<PathBuf>) -> cu::Result<Self> {
+        let paths = paths
+            .into_iter()
+            .map(|path| path.into_utf8())
+            .collect::<cu::Result<Vec<_>>>()?;
+        let literal_paths = paths.iter().cloned().collect();
+        Ok(Self {
+            literal_paths,
+            ..Self::new(holder, license, paths)
+        })
+    }
+
+    /// Get the file paths from [`Config::from_paths`], which are
+    /// not expanded as globs
+    pub fn literal_paths(&self) -> &BTreeSet<String> {
+        &self.literal_paths
+    }
+
+    /// Skip files matching any of the `excludes` globs, even if they
+    /// are matched by the globs in the config
+    pub fn with_excludes(mut self, excludes: Vec<String>) -> Self {
+        self.excludes = excludes;
+        self
+    }
+
+    /// Get the globs for files that are skipped
+    pub fn excludes(&self) -> &[String] {
+        &self.excludes
     }
 
     /// Build the config by reading the file specified, error if conflicts are detected
     ///
+    /// The file is parsed as TOML, JSON or YAML depending on the extension.
     /// The globs specified in the config file are relative to the parent directory
-    /// of `path`.
+    /// of `path`. Base configs in `extends` are built and merged first, with their
+    /// globs relative to their own parent directory.
     pub fn build(path: &str) -> cu::Result<Self> {
-        let raw = toml::parse::<TomlConfig>(&cu::fs::read_string(path)?)?;
+        Self::build_extending(path, &mut Vec::new())
+    }
+
+    /// Build the config at `path`, where `chain` is the configs (canonicalized)
+    /// currently being built that extend this config, for detecting cycles
+    fn build_extending(path: &str, chain: &mut Vec<PathBuf>) -> cu::Result<Self> {
+        let extension = Path::new(path).extension().and_then(|x| x.to_str());
+        let extension = extension.unwrap_or_default().to_ascii_lowercase();
+        if !matches!(extension.as_str(), "toml" | "json" | "yaml" | "yml") {
+            cu::bail!(
+                "unrecognized config file '{path}', expected a '.toml', '.json', '.yaml' or '.yml' file"
+            );
+        }
+        let content = cu::fs::read_string(path)?;
         let parent = Path::new(path)
             .parent()
             .context("failed to get parent path for config")?;
+        let mut raw = match extension.as_str() {
+            "json" => serde_json::from_str::<RawConfig>(&content)
+                .with_context(|| format!("failed to parse JSON config '{path}'"))?,
+            "yaml" | "yml" => serde_yaml::from_str::<RawConfig>(&content)
+                .with_context(|| format!("failed to parse YAML config '{path}'"))?,
+            _ => toml::parse::<RawConfig>(&content)?,
+        };
+        let Some(extends) = raw.extends.take() else {
+            return Self::from_raw(raw, parent, path);
+        };
+
+        let canonical_path = Path::new(path).canonicalize()?;
+        if let Some(i) = chain.iter().position(|x| *x == canonical_path) {
+            let cycle = chain[i..]
+                .iter()
+                .chain(std::iter::once(&canonical_path))
+                .map(|x| format!("'{}'", x.display()))
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            cu::bail!("cyclic extends detected: {cycle}");
+        }
+        chain.push(canonical_path);
+        let mut config = Self::default();
+        for base in extends.into_vec() {
+            let base_path = parent.join(&base).into_utf8()?;
+            let base_config = Self::build_extending(&base_path, chain)
+                .with_context(|| format!("failed to build '{base}' extended by '{path}'"))?;
+            config.absorb(base_config)?;
+        }
+        chain.pop();
+        let mut child = Self::from_raw(raw, parent, path)?;
+        // options in this config override the ones in the base configs
+        let options = std::mem::take(&mut child.options);
+        merge_options(&mut config.options, options, true)?;
+        config.absorb(child)?;
+        Ok(config)
+    }
+
+    /// Parse the config from the content of a TOML config file, error if conflicts are detected
+    ///
+    /// The globs specified in the config are relative to `parent`. `path` is only
+    /// used in messages to identify where the config is from.
+    pub fn parse(content: &str, parent: &Path, path: &str) -> cu::Result<Self> {
+        let raw = toml::parse::<RawConfig>(content)?;
+        if raw.extends.is_some() {
+            cu::bail!("'extends' is only supported in config files, but found in '{path}'");
+        }
+        Self::from_raw(raw, parent, path)
+    }
+
+    fn from_raw(raw: RawConfig, parent: &Path, path: &str) -> cu::Result<Self> {
+        let aliases = raw.aliases;
+        for (alias, license) in &aliases {
+            if aliases.contains_key(license) {
+                cu::bail!("alias '{alias}' in '{path}' maps to another alias '{license}'");
+            }
+            #[cfg(feature = "spdx")]
+            if let Err(e) = spdx::Expression::parse(license) {
+                cu::bail!(
+                    "alias '{alias}' in '{path}' maps to invalid SPDX license expression '{license}': {e}"
+                );
+            }
+            // without the spdx feature, only the structure of the expression is checked
+            #[cfg(not(feature = "spdx"))]
+            if !crate::format::is_license_expression(license) {
+                cu::bail!(
+                    "alias '{alias}' in '{path}' maps to invalid license expression '{license}'"
+                );
+            }
+        }
+        // the stored license is always the canonical one
+        let resolve = |license: String| aliases.get(&license).cloned().unwrap_or(license);
         let mut globs = BTreeMap::new();
-        for (holder, table) in raw.0 {
-            let holder = Arc::new(holder);
+        let mut headers = BTreeMap::new();
+        for (holder, mut table) in raw.holders {
+            // the holder can be read from a file, in which case
+            // the table name is only a label
+            let holder = match table.remove("holder_file") {
+                None => holder,
+                Some(holder_file) => read_holder_file(parent, &holder_file)
+                    .with_context(|| format!("failed to get holder for '{holder}' in '{path}'"))?,
+            };
+            // full header to use instead of the SPDX notice
+            if let Some(header_file) = table.remove("header_file") {
+                let header = read_header_file(parent, &header_file)
+                    .with_context(|| format!("failed to get header for '{holder}' in '{path}'"))?;
+                if headers.insert(holder.clone(), header).is_some() {
+                    cu::bail!("header_file specified multiple times for '{holder}' in '{path}'");
+                }
+            }
+            let holder = Arc::new(Holders::from(holder));
             for (glob, license) in table {
                 // globs in config files are resolved relative
                 // to the directory where the config file is in
                 let glob = parent.join(glob).into_utf8()?;
-                use std::collections::btree_map::Entry;
-                match globs.entry(glob) {
-                    Entry::Vacant(entry) => {
-                        entry.insert((Arc::clone(&holder), Arc::new(license)));
-                    }
-                    Entry::Occupied(entry) => {
-                        let glob = entry.key();
-                        let (curr_holder, curr_license) = entry.get();
-                        if *curr_holder == holder && curr_license.deref() == license.as_str() {
-                            cu::warn!("glob '{glob}' specified multiple times in '{path}'!");
-                            continue;
-                        }
-                        cu::error!("conflicting config specified for glob '{glob}':");
-                        cu::error!(
-                            "- in one config, it has holder '{holder}' and license '{license}'"
-                        );
-                        cu::error!(
-                            "- in another, it has holder '{curr_holder}' and license '{curr_license}'"
-                        );
-                        cu::bail!("conflicting config detected!");
-                    }
+                insert_glob(&mut globs, glob, &holder, resolve(license), path)?;
+            }
+        }
+        for (glob, multi) in raw.multi_holder {
+            if multi.holders.is_empty() {
+                cu::bail!("no holders specified for glob '{glob}' in multi_holder in '{path}'");
+            }
+            let holder = Arc::new(Holders::new(multi.holders));
+            let glob = parent.join(glob).into_utf8()?;
+            insert_glob(&mut globs, glob, &holder, resolve(multi.license), path)?;
+        }
+        let mut excludes = Vec::with_capacity(raw.exclude.len());
+        for glob in raw.exclude {
+            excludes.push(parent.join(glob).into_utf8()?);
+        }
+        let mut formats = BTreeMap::new();
+        for (ext, name) in raw.format {
+            let Some(format) = Format::from_name(&name) else {
+                let expected = crate::supported_formats()
+                    .iter()
+                    .map(|x| format!("'{}'", x.name()))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                cu::bail!(
+                    "unknown format '{name}' for extension '{ext}' in '{path}', expected one of {expected}"
+                );
+            };
+            formats.insert(ext, format);
+        }
+        let copyright_style = match raw.copyright_style {
+            None => CopyrightStyle::default(),
+            Some(name) => match CopyrightStyle::from_name(&name) {
+                Some(style) => style,
+                None => {
+                    cu::bail!(
+                        "unknown copyright_style '{name}' in '{path}', expected 'c' or 'unicode'"
+                    );
                 }
+            },
+        };
+        for tag in raw
+            .tags_before_license
+            .iter()
+            .chain(&raw.tags_after_license)
+        {
+            if !tag.contains(": ") {
+                cu::bail!("invalid tag line '{tag}' in '{path}', expected 'TAG: VALUE'");
             }
         }
-        Ok(Self { globs })
+        if raw.preamble.iter().any(|prefix| prefix.trim().is_empty()) {
+            cu::bail!("empty preamble prefix in '{path}'");
+        }
+        let mut declarations = BTreeMap::new();
+        for (ext, raw_declarations) in raw.declarations {
+            let prefixes = raw_declarations.prefixes;
+            if prefixes.is_empty() || prefixes.iter().any(|x| x.trim().is_empty()) {
+                cu::bail!("empty declaration prefix for extension '{ext}' in '{path}'");
+            }
+            let declarations_for_ext = Declarations {
+                prefixes,
+                max_lines: raw_declarations.max_lines.unwrap_or(1),
+            };
+            declarations.insert(ext, declarations_for_ext);
+        }
+        let options = NoticeOptions {
+            normalize_typography: raw.normalize_typography,
+            formats,
+            blank_after: raw.blank_after,
+            min_year: raw.min_year,
+            max_blank_lines_after: raw.max_blank_lines_after,
+            tags_before_license: raw.tags_before_license,
+            tags_after_license: raw.tags_after_license,
+            max_scan_lines: raw.max_scan_lines,
+            max_scan_bytes: raw.max_scan_bytes,
+            headers,
+            write_present: raw.write_present,
+            allow_stale_year: raw.allow_stale_year,
+            copyright_style,
+            preamble: raw.preamble,
+            declarations,
+            ..Default::default()
+        };
+        Ok(Self {
+            globs,
+            excludes,
+            literal_paths: BTreeSet::new(),
+            options,
+        })
+    }
+
+    /// Get the options for checking and fixing notices
+    pub fn notice_options(&self) -> &NoticeOptions {
+        &self.options
+    }
+
+    /// Get the options for checking and fixing notices for modification
+    pub fn notice_options_mut(&mut self) -> &mut NoticeOptions {
+        &mut self.options
+    }
+
+    /// Get the (holder, license) used by the most globs with a single holder.
+    /// If there's a tie, the smallest one is returned
+    pub fn most_common_entry(&self) -> Option<(&str, &str)> {
+        let mut counts = BTreeMap::new();
+        for (holders, license) in self.globs.values() {
+            let Some(holder) = holders.single() else {
+                continue;
+            };
+            *counts.entry((holder, license.as_str())).or_insert(0) += 1;
+        }
+        let max = counts.values().copied().max()?;
+        counts
+            .into_iter()
+            .find(|(_, count)| *count == max)
+            .map(|(entry, _)| entry)
     }
 
     /// Merge another config into self, error if conflicts are detected
     pub fn absorb(&mut self, other: Self) -> cu::Result<()> {
+        self.absorb_with(other, |_, _, _| Resolution::Error)
+    }
+
+    /// Merge another config into self, using `resolver` to decide what to do
+    /// when a glob has conflicting config in self (existing) and `other` (incoming).
+    ///
+    /// Options are merged one by one. Setting the same option to different
+    /// values in both configs is always an error.
+    pub fn absorb_with(
+        &mut self,
+        other: Self,
+        resolver: impl Fn(&str, GlobConfig<'_>, GlobConfig<'_>) -> Resolution,
+    ) -> cu::Result<()> {
+        merge_options(&mut self.options, other.options, false)?;
+        for exclude in other.excludes {
+            if !self.excludes.contains(&exclude) {
+                self.excludes.push(exclude);
+            }
+        }
+        self.literal_paths.extend(other.literal_paths);
         for (glob, (holder, license)) in other.globs {
             use std::collections::btree_map::Entry;
             match self.globs.entry(glob) {
                 Entry::Vacant(entry) => {
                     entry.insert((holder, license));
                 }
-                Entry::Occupied(entry) => {
+                Entry::Occupied(mut entry) => {
                     let glob = entry.key();
                     let (curr_holder, curr_license) = entry.get();
                     if *curr_holder == holder && curr_license.deref() == license.deref() {
                         cu::warn!("glob '{glob}' specified multiple times in multiple configs!");
                         continue;
                     }
-                    cu::error!(
-                        "conflicting config specified for glob '{glob}' in multiple configs:"
-                    );
-                    cu::error!("- in one config, it has holder '{holder}' and license '{license}'");
-                    cu::error!(
-                        "- in another, it has holder '{curr_holder}' and license '{curr_license}'"
-                    );
-                    cu::bail!("conflicting config detected!");
+                    let existing = GlobConfig {
+                        holder: curr_holder,
+                        license: curr_license,
+                    };
+                    let incoming = GlobConfig {
+                        holder: &holder,
+                        license: &license,
+                    };
+                    match resolver(glob, existing, incoming) {
+                        Resolution::KeepExisting => {
+                            cu::debug!("keeping existing config for glob '{glob}'");
+                        }
+                        Resolution::TakeIncoming => {
+                            cu::debug!("taking incoming config for glob '{glob}'");
+                            entry.insert((holder, license));
+                        }
+                        Resolution::Error => {
+                            cu::error!(
+                                "conflicting config specified for glob '{glob}' in multiple configs:"
+                            );
+                            cu::error!(
+                                "- in one config, it has holder {holder} and license '{license}'"
+                            );
+                            cu::error!(
+                                "- in another, it has holder {curr_holder} and license '{curr_license}'"
+                            );
+                            cu::bail!("conflicting config detected!");
+                        }
+                    }
                 }
             }
         }
@@ -124,10 +512,232 @@ impl Config {
     }
 }
 
+/// Merge the options in `incoming` into `existing`. An option not set (default)
+/// in one of them takes the value from the other. If both set an option to
+/// different values, `incoming` wins if `overriding`, otherwise it is an error.
+fn merge_options(
+    existing: &mut NoticeOptions,
+    incoming: NoticeOptions,
+    overriding: bool,
+) -> cu::Result<()> {
+    // destructure so new options can't be forgotten here
+    let NoticeOptions {
+        normalize_typography,
+        ignore_case_holder,
+        exact_license,
+        formats,
+        blank_after,
+        min_year,
+        max_blank_lines_after,
+        tags_before_license,
+        tags_after_license,
+        max_scan_lines,
+        max_scan_bytes,
+        headers,
+        write_present,
+        allow_stale_year,
+        copyright_style,
+        exact_copyright_style,
+        preamble,
+        declarations,
+        year,
+    } = incoming;
+    macro_rules! merge {
+        ($merge:ident, $field:ident) => {
+            merge!($merge, $field, stringify!($field))
+        };
+        ($merge:ident, $field:ident, $name:expr) => {
+            $merge($name, &mut existing.$field, $field, overriding)?
+        };
+    }
+    merge!(merge_option, normalize_typography);
+    merge!(merge_option, ignore_case_holder);
+    merge!(merge_option, exact_license);
+    merge!(merge_option_map, formats, "format");
+    merge!(merge_option_map, blank_after);
+    merge!(merge_option, min_year);
+    merge!(merge_option, max_blank_lines_after);
+    merge!(merge_option, tags_before_license);
+    merge!(merge_option, tags_after_license);
+    merge!(merge_option, max_scan_lines);
+    merge!(merge_option, max_scan_bytes);
+    merge!(merge_option_map, headers);
+    merge!(merge_option, write_present);
+    merge!(merge_option, allow_stale_year);
+    merge!(merge_option, copyright_style);
+    merge!(merge_option, exact_copyright_style);
+    merge!(merge_option, preamble);
+    merge!(merge_option_map, declarations);
+    merge!(merge_option, year);
+    Ok(())
+}
+
+/// Merge one option, see [`merge_options`]
+fn merge_option<T: Default + PartialEq + std::fmt::Debug>(
+    name: &str,
+    existing: &mut T,
+    incoming: T,
+    overriding: bool,
+) -> cu::Result<()> {
+    if incoming == T::default() || *existing == incoming {
+        return Ok(());
+    }
+    if overriding || *existing == T::default() {
+        *existing = incoming;
+        return Ok(());
+    }
+    cu::error!("conflicting option '{name}' specified in multiple configs:");
+    cu::error!("- in one config, it is {incoming:?}");
+    cu::error!("- in another, it is {existing:?}");
+    cu::bail!("conflicting config detected!");
+}
+
+/// Merge an option keyed by extension or holder one key at a time, see [`merge_options`]
+fn merge_option_map<T: PartialEq + std::fmt::Debug>(
+    name: &str,
+    existing: &mut BTreeMap<String, T>,
+    incoming: BTreeMap<String, T>,
+    overriding: bool,
+) -> cu::Result<()> {
+    use std::collections::btree_map::Entry;
+    for (key, value) in incoming {
+        match existing.entry(key) {
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+            }
+            Entry::Occupied(mut entry) => {
+                if *entry.get() == value {
+                    continue;
+                }
+                if overriding {
+                    entry.insert(value);
+                    continue;
+                }
+                let key = entry.key();
+                cu::error!("conflicting option '{name}.{key}' specified in multiple configs:");
+                cu::error!("- in one config, it is {value:?}");
+                cu::error!("- in another, it is {:?}", entry.get());
+                cu::bail!("conflicting config detected!");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Config for a glob, passed to the resolver in [`Config::absorb_with`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GlobConfig<'a> {
+    pub holder: &'a Holders,
+    pub license: &'a str,
+}
+
+/// How to resolve a conflicting glob when merging configs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// Keep the config already in self
+    KeepExisting,
+    /// Replace with the config being merged in
+    TakeIncoming,
+    /// Report the conflict as an error
+    Error,
+}
+
+/// Insert the config for a glob read from the config file at `path`,
+/// error if the glob is already specified with a different config
+fn insert_glob(
+    globs: &mut BTreeMap<String, (Arc<Holders>, Arc<String>)>,
+    glob: String,
+    holder: &Arc<Holders>,
+    license: String,
+    path: &str,
+) -> cu::Result<()> {
+    use std::collections::btree_map::Entry;
+    match globs.entry(glob) {
+        Entry::Vacant(entry) => {
+            entry.insert((Arc::clone(holder), Arc::new(license)));
+        }
+        Entry::Occupied(entry) => {
+            let glob = entry.key();
+            let (curr_holder, curr_license) = entry.get();
+            if curr_holder == holder && curr_license.deref() == license.as_str() {
+                cu::warn!("glob '{glob}' specified multiple times in '{path}'!");
+                return Ok(());
+            }
+            cu::error!("conflicting config specified for glob '{glob}':");
+            cu::error!("- in one config, it has holder {holder} and license '{license}'");
+            cu::error!("- in another, it has holder {curr_holder} and license '{curr_license}'");
+            cu::bail!("conflicting config detected!");
+        }
+    }
+    Ok(())
+}
+
+/// Read the holder from a `holder_file` specified in the config,
+/// relative to the directory of the config file
+fn read_holder_file(parent: &Path, holder_file: &str) -> cu::Result<String> {
+    let holder_path = parent.join(holder_file);
+    let content = cu::fs::read_string(&holder_path)
+        .with_context(|| format!("cannot read holder_file '{}'", holder_path.display()))?;
+    let holder = content.trim();
+    if holder.is_empty() {
+        cu::bail!("holder_file '{}' is empty", holder_path.display());
+    }
+    Ok(holder.to_string())
+}
+
+/// Read the full header template from a `header_file` specified in the config,
+/// relative to the directory of the config file
+fn read_header_file(parent: &Path, header_file: &str) -> cu::Result<String> {
+    let header_path = parent.join(header_file);
+    let content = cu::fs::read_string(&header_path)
+        .with_context(|| format!("cannot read header_file '{}'", header_path.display()))?;
+    if content.trim().is_empty() {
+        cu::bail!("header_file '{}' is empty", header_path.display());
+    }
+    Ok(content)
+}
+
 impl Config {
+    /// Check that all licenses in the config are valid SPDX license expressions,
+    /// such as `MIT OR Apache-2.0` or `GPL-2.0-only WITH Classpath-exception-2.0`
+    #[cfg(feature = "spdx")]
+    pub fn validate(&self) -> cu::Result<()> {
+        let mut invalid = 0;
+        for (glob, (_, license)) in &self.globs {
+            if let Err(e) = spdx::Expression::parse(license) {
+                cu::error!("invalid SPDX license expression '{license}' for glob '{glob}': {e}");
+                invalid += 1;
+            }
+        }
+        if invalid > 0 {
+            cu::bail!("found {invalid} invalid license(s) in the config.");
+        }
+        Ok(())
+    }
+
+    /// Expand all globs and get the matched files as canonicalized paths.
+    ///
+    /// Excluded files are included, since they are skipped on purpose
+    pub fn matched_paths(&self) -> cu::Result<BTreeSet<PathBuf>> {
+        let mut paths = BTreeSet::new();
+        for glob in self.globs.keys().chain(&self.excludes) {
+            if self.literal_paths.contains(glob) {
+                paths.insert(cu::check!(
+                    Path::new(glob).canonicalize(),
+                    "cannot find file '{glob}'"
+                )?);
+                continue;
+            }
+            for path in DiskFileSystem.glob(glob)? {
+                paths.insert(path.canonicalize()?);
+            }
+        }
+        Ok(paths)
+    }
+
     /// Iterate the resolve paths as (path, holder, license)
     #[allow(clippy::should_implement_trait)]
-    pub fn into_iter(self) -> impl Iterator<Item = (String, Arc<String>, Arc<String>)> {
+    pub fn into_iter(self) -> impl Iterator<Item = (String, Arc<Holders>, Arc<String>)> {
         // we can't implement the IntoIterator trait because
         // the map object has an unnamed function type
         self.globs
@@ -1,12 +1,15 @@
 // SPDX-License-Identifier: MIT
 // Copyright (c) 2025-2026 Pistonite
 
-use std::io::BufRead;
+use std::collections::BTreeMap;
+use std::io::{BufRead, Read};
 use std::path::Path;
 use std::sync::LazyLock;
 
 use cu::pre::*;
 
+use crate::{DiskFileSystem, FileSystem};
+
 const DEFAULT_YEAR: u32 = 2025; // The year this tool is made
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -15,15 +18,130 @@ pub enum Format {
     SlashSlash,
     /// The `# ...` format
     Hash,
+    /// The `(* ... *)` format
+    Paren,
+    /// The `<!-- ... -->` format
+    Html,
+    /// The `/* ... */` format
+    Block,
 }
 
 static HASH_FORMAT_EXTENSIONS: &[&str] = &[
-    "bash", "ini", "mk", "php", "phtml", "pl", "pm", "ps1", "psd1", "psm1", "py", "r", "rb", "sh",
-    "tcl", "toml", "yaml", "yml", "zsh",
+    "bash", "hcl", "ini", "mk", "php", "phtml", "pl", "pm", "ps1", "psd1", "psm1", "py", "r", "rb",
+    "sh", "tcl", "tf", "tfvars", "toml", "yaml", "yml", "zsh",
+];
+
+static PAREN_FORMAT_EXTENSIONS: &[&str] = &["fs", "fsi", "ml", "mli", "pas"];
+
+static HTML_FORMAT_EXTENSIONS: &[&str] = &[
+    "htm", "html", "markdown", "md", "svg", "vue", "xml", "xsd", "xsl",
+];
+
+static BLOCK_FORMAT_EXTENSIONS: &[&str] = &["c", "css", "sql"];
+
+/// Names of files that use the `#` format, regardless of the extension
+static HASH_FORMAT_FILE_NAMES: &[&str] = &[
+    ".bash_profile",
+    ".bashrc",
+    ".dockerignore",
+    ".editorconfig",
+    ".gitattributes",
+    ".gitignore",
+    ".gitmodules",
+    ".npmrc",
+    ".profile",
+    ".zprofile",
+    ".zshrc",
+    "APKBUILD",
+    "BUILD",
+    "BUILD.bazel",
+    "Berksfile",
+    "Brewfile",
+    "CMakeLists.txt",
+    "Caddyfile",
+    "Capfile",
+    "Containerfile",
+    "Dangerfile",
+    "Dockerfile",
+    "Earthfile",
+    "GNUmakefile",
+    "Gemfile",
+    "Guardfile",
+    "Justfile",
+    "Makefile",
+    "PKGBUILD",
+    "Pipfile",
+    "Podfile",
+    "Procfile",
+    "Puppetfile",
+    "Rakefile",
+    "Snakefile",
+    "Thorfile",
+    "Tiltfile",
+    "Vagrantfile",
+    "WORKSPACE",
+    "WORKSPACE.bazel",
+    "justfile",
+    "makefile",
+    "requirements.txt",
+];
+
+/// Extensions of files that use the `//` format. Files with an extension
+/// not in any of the lists also use the `//` format, unless the shebang says otherwise
+static SLASH_FORMAT_EXTENSIONS: &[&str] = &[
+    "cc", "cjs", "cpp", "cs", "cts", "cxx", "dart", "go", "groovy", "h", "hpp", "java", "js",
+    "jsx", "kt", "kts", "mjs", "mts", "proto", "rs", "scala", "swift", "ts", "tsx", "zig",
+];
+
+/// Interpreters in a shebang for scripts that use the `//` format.
+/// Scripts with other interpreters use the `#` format
+static SLASH_FORMAT_INTERPRETERS: &[&str] = &["bun", "deno", "node", "ts-node", "tsx", "zx"];
+
+/// Extensions of markdown files, where fenced code blocks are skipped
+/// when looking for notices
+static MARKDOWN_EXTENSIONS: &[&str] = &["markdown", "md"];
+
+/// Extensions of files that cannot carry a license notice,
+/// either because they are binary or the format has no comments
+static UNSUPPORTED_EXTENSIONS: &[&str] = &[
+    "bin", "bmp", "dll", "exe", "gif", "gz", "ico", "jpeg", "jpg", "json", "pdf", "png", "so",
+    "tar", "ttf", "wasm", "webp", "woff", "woff2", "zip",
 ];
 
+/// Get all the comment formats supported by the tool
+pub fn supported_formats() -> &'static [Format] {
+    &[
+        Format::SlashSlash,
+        Format::Hash,
+        Format::Paren,
+        Format::Html,
+        Format::Block,
+    ]
+}
+
+/// Check if the file at `path` is handled by the tool, i.e. the format
+/// returned by [`Format::from_path`] is able to carry a license notice
+/// in that file.
+///
+/// This only looks at the path and does not access the file system.
+pub fn is_supported(path: &Path) -> bool {
+    let Some(ext) = path.extension().and_then(|x| x.to_str()) else {
+        return true;
+    };
+    // binary files often have upper case extensions, like `logo.PNG`
+    let ext = ext.to_ascii_lowercase();
+    UNSUPPORTED_EXTENSIONS.binary_search(&ext.as_str()).is_err()
+}
+
 impl Format {
     pub fn from_path(path: &Path) -> Self {
+        let file_name = path
+            .file_name()
+            .and_then(|x| x.to_str())
+            .unwrap_or_default();
+        if HASH_FORMAT_FILE_NAMES.binary_search(&file_name).is_ok() {
+            return Self::Hash;
+        }
         let Some(ext) = path.extension().and_then(|x| x.to_str()) else {
             return Self::SlashSlash;
         };
@@ -31,33 +149,217 @@ impl Format {
         if HASH_FORMAT_EXTENSIONS.binary_search(&ext).is_ok() {
             return Self::Hash;
         }
+        if PAREN_FORMAT_EXTENSIONS.binary_search(&ext).is_ok() {
+            return Self::Paren;
+        }
+        if HTML_FORMAT_EXTENSIONS.binary_search(&ext).is_ok() {
+            return Self::Html;
+        }
+        if BLOCK_FORMAT_EXTENSIONS.binary_search(&ext).is_ok() {
+            return Self::Block;
+        }
         Self::SlashSlash
     }
 
-    /// Strip the license line if it's the right format.
-    /// Return the SPDX id
-    pub fn check_strip_license_line(self, line: &str) -> Option<&str> {
+    /// Get the format for the file at `path` with `first_line`, like [`Format::from_path`].
+    ///
+    /// For files without an extension or with an unknown extension (and not a well-known
+    /// file name like `Dockerfile`), the interpreter in the shebang is used to pick the
+    /// format, so a script with `#!/bin/sh` uses the `#` format. Files without either
+    /// use the `//` format
+    pub fn detect_from_content(path: &Path, first_line: &str) -> Self {
+        let format = Self::from_path(path);
+        let known_slash = path
+            .extension()
+            .and_then(|x| x.to_str())
+            .is_some_and(|ext| SLASH_FORMAT_EXTENSIONS.binary_search(&ext).is_ok());
+        if format != Self::SlashSlash || known_slash {
+            return format;
+        }
+        let first_line = first_line.strip_prefix(BOM).unwrap_or(first_line);
+        // `#![...]` is an inner attribute in Rust, not a shebang
+        let Some(command) = first_line.strip_prefix("#!") else {
+            return format;
+        };
+        if command.starts_with('[') {
+            return format;
+        }
+        // the interpreter is the program, or the program run by `env`
+        let mut args = command
+            .split_whitespace()
+            .map(|arg| arg.rsplit('/').next().unwrap_or(arg));
+        let mut interpreter = args.next().unwrap_or_default();
+        if interpreter == "env" {
+            interpreter = args.find(|arg| !arg.starts_with('-')).unwrap_or_default();
+        }
+        if SLASH_FORMAT_INTERPRETERS.contains(&interpreter) {
+            return Self::SlashSlash;
+        }
+        Self::Hash
+    }
+
+    /// Get the format from the name used in the config file
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "slash" => Some(Self::SlashSlash),
+            "hash" => Some(Self::Hash),
+            "paren" => Some(Self::Paren),
+            "html" => Some(Self::Html),
+            "block" => Some(Self::Block),
+            _ => None,
+        }
+    }
+
+    /// Get the name of the format used in the config file
+    pub fn name(self) -> &'static str {
         match self {
-            Self::SlashSlash => line.strip_prefix("// SPDX-License-Identifier: "),
-            Self::Hash => line.strip_prefix("# SPDX-License-Identifier: "),
+            Self::SlashSlash => "slash",
+            Self::Hash => "hash",
+            Self::Paren => "paren",
+            Self::Html => "html",
+            Self::Block => "block",
         }
     }
 
-    /// Strip the copyright line if it's the right format.
-    /// Return "YYYY[-YYYY] HOLDER"
-    pub fn check_strip_copyright_line(self, line: &str) -> Option<&str> {
+    /// Get the start of a comment line, including the space after the comment token
+    pub fn prefix(self) -> &'static str {
         match self {
-            Self::SlashSlash => line.strip_prefix("// Copyright (c) "),
-            Self::Hash => line.strip_prefix("# Copyright (c) "),
+            Self::SlashSlash => "// ",
+            Self::Hash => "# ",
+            Self::Paren => "(* ",
+            Self::Html => "<!-- ",
+            Self::Block => "/* ",
         }
     }
 
-    /// Check if the line starts with sentinel comment
-    pub fn starts_with_sentinel(self, line: &str) -> bool {
+    /// Get the end of a comment line, including the space before the comment token.
+    /// Empty if the comment goes until the end of the line
+    pub fn suffix(self) -> &'static str {
         match self {
-            Self::SlashSlash => line.starts_with("// * * * * *"),
-            Self::Hash => line.starts_with("# * * * * *"),
+            Self::SlashSlash | Self::Hash => "",
+            Self::Paren => " *)",
+            Self::Html => " -->",
+            Self::Block => " */",
+        }
+    }
+
+    /// Strip the comment prefix and suffix, then the `tag`, if the line is a comment
+    /// in this format that starts with `tag`
+    fn strip_comment<'a>(self, line: &'a str, tag: &str) -> Option<&'a str> {
+        if self == Self::Html {
+            // whitespace inside the wrappers is not significant
+            return line
+                .strip_prefix("<!--")?
+                .strip_suffix("-->")?
+                .trim()
+                .strip_prefix(tag);
+        }
+        line.strip_prefix(self.prefix())?
+            .strip_prefix(tag)?
+            .strip_suffix(self.suffix())
+    }
+
+    /// Check if the line is a declaration that must stay at the top of the file,
+    /// like `<?xml ...?>` or `<!DOCTYPE ...>`. The notice is placed after it
+    pub fn is_declaration_line(self, line: &str) -> bool {
+        if self != Self::Html {
+            return false;
+        }
+        let line = line.trim_start();
+        line.starts_with("<?xml")
+            || line
+                .get(..9)
+                .is_some_and(|x| x.eq_ignore_ascii_case("<!doctype"))
+    }
+
+    /// Strip the comment prefix and suffix if the line is a comment in this format,
+    /// tolerating missing or extra whitespace inside the comment tokens
+    fn strip_comment_loose(self, line: &str) -> Option<&str> {
+        let inner = if self == Self::Html {
+            line.strip_prefix("<!--")?.strip_suffix("-->")?
+        } else {
+            line.strip_prefix(self.prefix().trim_end())?
+                .strip_suffix(self.suffix().trim_start())?
+        };
+        Some(inner.trim())
+    }
+
+    /// Check if the line is exactly the comment of `text` with the canonical spacing
+    fn is_canonical_line(self, line: &str, text: &str) -> bool {
+        if self == Self::Html {
+            // whitespace inside the wrappers is not significant
+            return self.strip_comment_loose(line) == Some(text);
         }
+        line.strip_prefix(self.prefix())
+            .and_then(|x| x.strip_suffix(self.suffix()))
+            == Some(text)
+    }
+
+    /// Strip the license line if it's the right format.
+    /// Return the SPDX id.
+    ///
+    /// Whitespace after the comment token and around the colon is not significant,
+    /// use [`is_canonical_license_line`](Self::is_canonical_license_line) to check the spacing
+    pub fn check_strip_license_line(self, line: &str) -> Option<&str> {
+        let rest = self
+            .strip_comment_loose(line)?
+            .strip_prefix("SPDX-License-Identifier")?
+            .trim_start()
+            .strip_prefix(':')?;
+        Some(rest.trim_start())
+    }
+
+    /// Strip the copyright line if it's the right format.
+    /// Return "YYYY[-YYYY] HOLDER".
+    ///
+    /// Both `(c)` and `©` are accepted. Whitespace after the comment token and around
+    /// the mark is not significant, use
+    /// [`is_canonical_copyright_line`](Self::is_canonical_copyright_line) to check the spacing
+    pub fn check_strip_copyright_line(self, line: &str) -> Option<&str> {
+        let rest = self
+            .strip_comment_loose(line)?
+            .strip_prefix("Copyright")?
+            .trim_start();
+        let rest = CopyrightStyle::all()
+            .iter()
+            .find_map(|style| rest.strip_prefix(style.mark()))?;
+        Some(rest.trim_start())
+    }
+
+    /// Check if the license line has the canonical spacing, i.e. it's what fixing would write
+    pub fn is_canonical_license_line(self, line: &str) -> bool {
+        self.check_strip_license_line(line).is_some_and(|license| {
+            self.is_canonical_line(line, &format!("SPDX-License-Identifier: {license}"))
+        })
+    }
+
+    /// Check if the copyright line is in the `style` with the canonical spacing,
+    /// i.e. it's what fixing would write
+    pub fn is_canonical_copyright_line(self, line: &str, style: CopyrightStyle) -> bool {
+        self.check_strip_copyright_line(line).is_some_and(|info| {
+            self.is_canonical_line(line, &format!("Copyright {} {info}", style.mark()))
+        })
+    }
+
+    /// Check if the line is the comment of [`IGNORE_MARKER`] in this format,
+    /// like `// lisensor:ignore` or `# lisensor:ignore`
+    pub fn is_ignore_marker(self, line: &str) -> bool {
+        self.strip_comment_loose(line) == Some(IGNORE_MARKER)
+    }
+
+    /// Check if the line starts with sentinel comment
+    pub fn starts_with_sentinel(self, line: &str) -> bool {
+        line.strip_prefix(self.prefix())
+            .is_some_and(|x| x.starts_with("* * * * *"))
+    }
+
+    /// Check if the line is a comment in this format for the SPDX tag of `tag`,
+    /// regardless of its value. For example, `SPDX-FileType: SOURCE` matches
+    /// any `SPDX-FileType` line
+    pub fn is_tag_line(self, line: &str, tag: &str) -> bool {
+        let key = tag.split_once(':').map(|(key, _)| key).unwrap_or(tag);
+        self.strip_comment(line, key)
+            .is_some_and(|rest| rest.starts_with(':'))
     }
 
     /// Format the license notice into a buffer
@@ -68,136 +370,1616 @@ impl Format {
         license: &str,
         is_crlf: bool,
         buf: &mut String,
+    ) -> cu::Result<()> {
+        self.format_with_options(
+            year_start,
+            holder,
+            license,
+            &NoticeOptions::default(),
+            is_crlf,
+            buf,
+        )
+    }
+
+    /// Format the license notice into a buffer, including the extra
+    /// tag lines in `options`
+    pub fn format_with_options(
+        self,
+        year_start: u32,
+        holder: &str,
+        license: &str,
+        options: &NoticeOptions,
+        is_crlf: bool,
+        buf: &mut String,
+    ) -> cu::Result<()> {
+        self.format_with_holders(&[(year_start, holder)], license, options, is_crlf, buf)
+    }
+
+    /// Format the license notice into a buffer, with a copyright line
+    /// for each (start year, holder) in `holders`
+    pub fn format_with_holders(
+        self,
+        holders: &[(u32, &str)],
+        license: &str,
+        options: &NoticeOptions,
+        is_crlf: bool,
+        buf: &mut String,
     ) -> cu::Result<()> {
         use std::fmt::Write as _;
-        let year_end = current_year();
+        let year_end = options.current_year();
         let le = if is_crlf { "\r\n" } else { "\n" };
+        let (p, s) = (self.prefix(), self.suffix());
+        let c = options.copyright_style.mark();
+        // the written lines must not have trailing whitespace
+        let license = license.trim();
+        for tag in &options.tags_before_license {
+            let tag = tag.trim();
+            write!(buf, "{p}{tag}{s}{le}")?;
+        }
+        write!(buf, "{p}SPDX-License-Identifier: {license}{s}{le}")?;
+        for tag in &options.tags_after_license {
+            let tag = tag.trim();
+            write!(buf, "{p}{tag}{s}{le}")?;
+        }
+        for &(year_start, holder) in holders {
+            let holder = holder.trim();
+            if options.write_present {
+                write!(buf, "{p}Copyright {c} {year_start}-present {holder}{s}{le}")?;
+            } else if year_start == year_end {
+                write!(buf, "{p}Copyright {c} {year_start} {holder}{s}{le}")?;
+            } else {
+                write!(
+                    buf,
+                    "{p}Copyright {c} {year_start}-{year_end} {holder}{s}{le}"
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Options for checking and fixing license notices
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct NoticeOptions {
+    /// Replace typographic characters (curly quotes, en/em dashes) in the holder
+    /// with their ASCII equivalent before comparing
+    pub normalize_typography: bool,
+    /// Compare the holder case-insensitively
+    pub ignore_case_holder: bool,
+    /// Compare the license exactly, instead of as SPDX expressions where
+    /// the order of the operands of `AND` and `OR` doesn't matter
+    pub exact_license: bool,
+    /// Extension -> format to use instead of the built-in defaults
+    pub formats: BTreeMap<String, Format>,
+    /// Extension -> if a blank line should be inserted between the notice
+    /// and the rest of the content when fixing (default is `true`)
+    pub blank_after: BTreeMap<String, bool>,
+    /// Copyright start years before this are treated as malformed
+    pub min_year: Option<u32>,
+    /// Maximum number of consecutive blank lines after the notice.
+    /// Fixing will collapse extra blank lines
+    pub max_blank_lines_after: Option<usize>,
+    /// Extra SPDX tag lines (such as `SPDX-FileType: SOURCE`) required
+    /// before the license line, in order
+    pub tags_before_license: Vec<String>,
+    /// Extra SPDX tag lines required between the license line
+    /// and the copyright line, in order
+    pub tags_after_license: Vec<String>,
+    /// Maximum number of lines to read from the start of a file when checking.
+    /// If not set, [`DEFAULT_MAX_SCAN_LINES`] is used
+    pub max_scan_lines: Option<usize>,
+    /// Maximum number of bytes to read from the start of a file when checking,
+    /// so a huge line (like in a minified file) is not read entirely.
+    /// If not set, [`DEFAULT_MAX_SCAN_BYTES`] is used
+    pub max_scan_bytes: Option<u64>,
+    /// Holder -> full header template to use instead of the SPDX notice.
+    /// `{year}`, `{holder}` and `{license}` in the template are substituted
+    pub headers: BTreeMap<String, String>,
+    /// Write `present` as the end year (like `2020-present`) when fixing,
+    /// instead of the current year
+    pub write_present: bool,
+    /// Accept copyright years ending before the current year when checking.
+    /// Fixing still updates them
+    pub allow_stale_year: bool,
+    /// The copyright mark to use when fixing
+    pub copyright_style: CopyrightStyle,
+    /// Report copyright lines that are not in [`copyright_style`](Self::copyright_style).
+    /// Otherwise, both styles are accepted when checking
+    pub exact_copyright_style: bool,
+    /// Prefixes of lines (such as `// @generated` or a vim modeline) at the start
+    /// of the file that must stay before the notice, like a shebang
+    pub preamble: Vec<String>,
+    /// Extension -> declaration lines (such as `package com.foo;`) that can
+    /// precede the notice in files with that extension
+    pub declarations: BTreeMap<String, Declarations>,
+    /// The year to use as the current year when checking and fixing,
+    /// instead of the system clock, for reproducible output
+    pub year: Option<u32>,
+}
+
+/// Leading declaration lines that can precede the notice, see [`NoticeOptions::declarations`]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Declarations {
+    /// Prefixes of the declaration lines, such as `package ` or `module `
+    pub prefixes: Vec<String>,
+    /// Maximum number of declaration lines before the notice
+    pub max_lines: usize,
+}
+
+impl Declarations {
+    /// Check if the line is a declaration line
+    pub fn matches(&self, line: &str) -> bool {
+        self.prefixes
+            .iter()
+            .any(|prefix| line.starts_with(prefix.as_str()))
+    }
+}
+
+/// The mark after `Copyright` in the copyright line
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CopyrightStyle {
+    /// `Copyright (c)`
+    #[default]
+    C,
+    /// `Copyright ©`
+    Unicode,
+}
+
+impl CopyrightStyle {
+    /// Get all the styles
+    pub fn all() -> &'static [Self] {
+        &[Self::C, Self::Unicode]
+    }
+
+    /// Get the style from the name used in the config file
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "c" => Some(Self::C),
+            "unicode" => Some(Self::Unicode),
+            _ => None,
+        }
+    }
+
+    /// Get the name of the style used in the config file
+    pub fn name(self) -> &'static str {
         match self {
-            Self::SlashSlash => {
-                if year_start == year_end {
-                    write!(
-                        buf,
-                        "// SPDX-License-Identifier: {license}{le}// Copyright (c) {year_start} {holder}{le}"
-                    )?;
-                } else {
-                    write!(
-                        buf,
-                        "// SPDX-License-Identifier: {license}{le}// Copyright (c) {year_start}-{year_end} {holder}{le}"
-                    )?;
-                }
+            Self::C => "c",
+            Self::Unicode => "unicode",
+        }
+    }
+
+    /// Get the mark written after `Copyright`
+    pub fn mark(self) -> &'static str {
+        match self {
+            Self::C => "(c)",
+            Self::Unicode => "©",
+        }
+    }
+}
+
+/// Files with this marker in a comment as the first line are not checked,
+/// and fixing never inserts a notice into them
+pub const IGNORE_MARKER: &str = "lisensor:ignore";
+
+/// Default for [`NoticeOptions::max_scan_lines`]
+pub const DEFAULT_MAX_SCAN_LINES: usize = 1000;
+
+/// Default for [`NoticeOptions::max_scan_bytes`]
+pub const DEFAULT_MAX_SCAN_BYTES: u64 = 1024 * 1024;
+
+impl NoticeOptions {
+    /// Get the options with all lenient comparisons disabled.
+    ///
+    /// In fix mode, this is used for checking if a file needs to be fixed,
+    /// so notices accepted by lenient comparisons are normalized.
+    pub fn strict(&self) -> Self {
+        Self {
+            normalize_typography: false,
+            ignore_case_holder: false,
+            exact_license: true,
+            exact_copyright_style: true,
+            allow_stale_year: false,
+            ..self.clone()
+        }
+    }
+
+    /// Get the current year, which is [`year`](Self::year) if set
+    pub fn current_year(&self) -> u32 {
+        self.year.unwrap_or_else(current_year)
+    }
+
+    /// Get the format for the file at `path`, taking overrides into account
+    pub fn format_for(&self, path: &Path) -> Format {
+        path.extension()
+            .and_then(|x| x.to_str())
+            .and_then(|ext| self.formats.get(ext))
+            .copied()
+            .unwrap_or_else(|| Format::from_path(path))
+    }
+
+    /// Get the format for the file at `path` with `first_line`, taking overrides
+    /// into account. See [`Format::detect_from_content`]
+    pub fn format_for_content(&self, path: &Path, first_line: &str) -> Format {
+        path.extension()
+            .and_then(|x| x.to_str())
+            .and_then(|ext| self.formats.get(ext))
+            .copied()
+            .unwrap_or_else(|| Format::detect_from_content(path, first_line))
+    }
+
+    /// Check if the copyright start year is too old to be plausible
+    pub fn is_year_too_old(&self, year_start: u32) -> bool {
+        self.min_year.is_some_and(|min_year| year_start < min_year)
+    }
+
+    /// Check if a blank line should be inserted after the notice for the file at `path`
+    pub fn blank_after_for(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|x| x.to_str())
+            .and_then(|ext| self.blank_after.get(ext))
+            .copied()
+            .unwrap_or(true)
+    }
+
+    /// Get the full header template for the holder, if any
+    pub fn header_for(&self, holder: &str) -> Option<&str> {
+        self.headers.get(holder).map(|x| x.as_str())
+    }
+
+    /// Get the full header for the holders, with the holder and license substituted.
+    /// Headers are only used for a single holder
+    fn full_header(&self, holders: &Holders, license: &str) -> Option<Header> {
+        let holder = holders.single()?;
+        let template = self.header_for(holder)?;
+        Some(Header::new(template, holder, license))
+    }
+
+    /// Check if the line must stay before the notice. `first` is if the line
+    /// is the first line of the file
+    fn is_preamble(&self, line: &str, format: Format, first: bool) -> bool {
+        (first && is_preamble_line(line, format))
+            || self
+                .preamble
+                .iter()
+                .any(|prefix| line.starts_with(prefix.as_str()))
+    }
+
+    /// Get the declaration lines that can precede the notice for the file at `path`
+    pub fn declarations_for(&self, path: &Path) -> Option<&Declarations> {
+        let ext = path.extension()?.to_str()?;
+        self.declarations.get(ext)
+    }
+
+    /// Get the number of lines at the start of the content that must stay before the notice.
+    /// Blank lines are only included if there are more preamble lines after them.
+    /// If `path` is known, the declarations for its extension are included
+    fn preamble_len(&self, file_content: &str, format: Format, path: Option<&Path>) -> usize {
+        let declarations = path.and_then(|path| self.declarations_for(path));
+        let mut declaration_lines = 0;
+        let mut len = 0;
+        let mut blank_lines = 0;
+        for (i, line) in file_content.lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            if i > 0 && line.trim().is_empty() {
+                blank_lines += 1;
+                continue;
             }
-            Self::Hash => {
-                if year_start == year_end {
-                    write!(
-                        buf,
-                        "# SPDX-License-Identifier: {license}{le}# Copyright (c) {year_start} {holder}{le}"
-                    )?;
-                } else {
-                    write!(
-                        buf,
-                        "# SPDX-License-Identifier: {license}{le}# Copyright (c) {year_start}-{year_end} {holder}{le}"
-                    )?;
-                }
+            if declarations.is_some_and(|d| declaration_lines < d.max_lines && d.matches(line)) {
+                declaration_lines += 1;
+            } else if !self.is_preamble(line, format, i == 0) {
+                break;
             }
+            len += blank_lines + 1;
+            blank_lines = 0;
         }
-        Ok(())
+        len
+    }
+
+    /// Check if the line is one of the extra tag lines, with any value
+    fn is_any_tag_line(&self, line: &str, format: Format) -> bool {
+        self.tags_before_license
+            .iter()
+            .chain(&self.tags_after_license)
+            .any(|tag| format.is_tag_line(line, tag))
+    }
+
+    /// Check if the license found in the file matches the expected license
+    pub fn license_matches(&self, actual: &str, expected: &str) -> bool {
+        if actual == expected {
+            return true;
+        }
+        // different plain identifiers are never equivalent
+        let is_plain = |license: &str| !license.contains([' ', '(']);
+        if self.exact_license || (is_plain(actual) && is_plain(expected)) {
+            return false;
+        }
+        match (LicenseExpr::parse(actual), LicenseExpr::parse(expected)) {
+            (Some(actual), Some(expected)) => actual == expected,
+            _ => false,
+        }
+    }
+
+    /// Check if the holder found in the file matches the expected holder
+    pub fn holder_matches(&self, actual: &str, expected: &str) -> bool {
+        if actual == expected {
+            return true;
+        }
+        match (self.normalize_typography, self.ignore_case_holder) {
+            (false, false) => false,
+            (true, false) => normalize_typography(actual) == normalize_typography(expected),
+            (false, true) => actual.to_lowercase() == expected.to_lowercase(),
+            (true, true) => {
+                normalize_typography(actual).to_lowercase()
+                    == normalize_typography(expected).to_lowercase()
+            }
+        }
+    }
+}
+
+/// SPDX license expression, normalized so equivalent expressions are equal
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum LicenseExpr {
+    /// A license, with the exception if any (like `GPL-2.0-only WITH Classpath-exception-2.0`)
+    Id(String),
+    /// Sorted operands of `AND`
+    And(Vec<LicenseExpr>),
+    /// Sorted operands of `OR`
+    Or(Vec<LicenseExpr>),
+}
+
+type LicenseTokens<'a> = std::iter::Peekable<std::str::SplitWhitespace<'a>>;
+
+impl LicenseExpr {
+    /// Parse the expression, returns `None` if it's not valid
+    fn parse(expr: &str) -> Option<Self> {
+        let spaced = expr.replace('(', " ( ").replace(')', " ) ");
+        let mut tokens = spaced.split_whitespace().peekable();
+        let parsed = Self::parse_or(&mut tokens)?;
+        tokens.next().is_none().then_some(parsed)
+    }
+
+    fn parse_or(tokens: &mut LicenseTokens<'_>) -> Option<Self> {
+        let mut operands = vec![Self::parse_and(tokens)?];
+        while tokens.next_if(|x| x.eq_ignore_ascii_case("OR")).is_some() {
+            operands.push(Self::parse_and(tokens)?);
+        }
+        Some(Self::join(operands, true))
+    }
+
+    fn parse_and(tokens: &mut LicenseTokens<'_>) -> Option<Self> {
+        let mut operands = vec![Self::parse_primary(tokens)?];
+        while tokens.next_if(|x| x.eq_ignore_ascii_case("AND")).is_some() {
+            operands.push(Self::parse_primary(tokens)?);
+        }
+        Some(Self::join(operands, false))
+    }
+
+    fn parse_primary(tokens: &mut LicenseTokens<'_>) -> Option<Self> {
+        let token = tokens.next()?;
+        if token == "(" {
+            let parsed = Self::parse_or(tokens)?;
+            return (tokens.next()? == ")").then_some(parsed);
+        }
+        if !is_license_id(token) {
+            return None;
+        }
+        if tokens.next_if(|x| x.eq_ignore_ascii_case("WITH")).is_none() {
+            return Some(Self::Id(token.to_string()));
+        }
+        let exception = tokens.next().filter(|x| is_license_id(x))?;
+        Some(Self::Id(format!("{token} WITH {exception}")))
+    }
+
+    /// Combine the operands of `OR` (or `AND` if not `is_or`), flattening
+    /// the nested operations of the same kind
+    fn join(mut operands: Vec<Self>, is_or: bool) -> Self {
+        if operands.len() == 1 {
+            return operands.remove(0);
+        }
+        let mut flat = Vec::with_capacity(operands.len());
+        for operand in operands {
+            match operand {
+                Self::Or(inner) if is_or => flat.extend(inner),
+                Self::And(inner) if !is_or => flat.extend(inner),
+                operand => flat.push(operand),
+            }
+        }
+        flat.sort();
+        if is_or {
+            Self::Or(flat)
+        } else {
+            Self::And(flat)
+        }
+    }
+}
+
+/// Check if `expr` is a valid license expression, with the operators and
+/// parentheses in the right places. The license ids are not checked
+#[cfg(not(feature = "spdx"))]
+pub(crate) fn is_license_expression(expr: &str) -> bool {
+    LicenseExpr::parse(expr).is_some()
+}
+
+/// Check if the token in a license expression is a license or exception id,
+/// not a parenthesis or an operator
+fn is_license_id(token: &str) -> bool {
+    token != "("
+        && token != ")"
+        && !["AND", "OR", "WITH"]
+            .iter()
+            .any(|op| token.eq_ignore_ascii_case(op))
+}
+
+/// Replace common typographic characters with their ASCII equivalent
+fn normalize_typography(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' => '\'',
+            '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' => '"',
+            '\u{2010}'..='\u{2015}' => '-',
+            c => c,
+        })
+        .collect()
+}
+
+pub fn check_file(
+    path: &Path,
+    expected_holder: &Holders,
+    expected_license: &str,
+    options: &NoticeOptions,
+) -> cu::Result<()> {
+    check_file_with_fs(
+        &DiskFileSystem,
+        path,
+        expected_holder,
+        expected_license,
+        options,
+    )
+}
+
+/// Check the file at `path`, accessed through `fs`
+pub fn check_file_with_fs(
+    fs: &dyn FileSystem,
+    path: &Path,
+    expected_holder: &Holders,
+    expected_license: &str,
+    options: &NoticeOptions,
+) -> cu::Result<()> {
+    let max_scan_bytes = options.max_scan_bytes.unwrap_or(DEFAULT_MAX_SCAN_BYTES);
+    let mut reader = fs.reader(path)?.take(max_scan_bytes);
+    let format = format_for_reader(&mut reader, path, options)?;
+    check_lines(
+        capped_lines(reader),
+        format,
+        path,
+        expected_holder,
+        expected_license,
+        options,
+    )
+}
+
+/// Get the format for the file at `path`, accessed through `fs`, like
+/// [`NoticeOptions::format_for_content`]. If the file can't be read,
+/// only the path is used
+pub(crate) fn format_for_file(fs: &dyn FileSystem, path: &Path, options: &NoticeOptions) -> Format {
+    let format = fs
+        .reader(path)
+        .and_then(|mut reader| format_for_reader(&mut reader, path, options));
+    format.unwrap_or_else(|_| options.format_for(path))
+}
+
+/// Get the format for the file at `path` with the content in `reader`,
+/// without consuming the content
+fn format_for_reader(
+    reader: &mut impl BufRead,
+    path: &Path,
+    options: &NoticeOptions,
+) -> cu::Result<Format> {
+    // the first line is usually in the buffer already, and only used for the shebang
+    let buffer = reader.fill_buf()?;
+    let first_line = buffer.split(|b| *b == b'\n').next().unwrap_or_default();
+    Ok(options.format_for_content(path, &String::from_utf8_lossy(first_line)))
+}
+
+impl Format {
+    /// Check the content in memory as if it's a file in this format.
+    ///
+    /// This is the same as [`check_file`], but without accessing the file system
+    pub fn check_content(
+        self,
+        content: &str,
+        expected_holder: &Holders,
+        expected_license: &str,
+        options: &NoticeOptions,
+    ) -> cu::Result<()> {
+        check_lines(
+            content.lines().map(|x| Ok(x.to_string())),
+            self,
+            Path::new("<content>"),
+            expected_holder,
+            expected_license,
+            options,
+        )
+    }
+
+    /// Compute the fixed content in memory as if it's a file in this format.
+    ///
+    /// This is the same as [`fixed_file_content`], but without accessing the file system.
+    /// Since there's no file extension, a blank line is always inserted after the notice
+    /// and fenced code blocks are not skipped
+    pub fn fix_content(
+        self,
+        content: &str,
+        expected_holder: &Holders,
+        expected_license: &str,
+        options: &NoticeOptions,
+    ) -> cu::Result<String> {
+        fixed_content(
+            content,
+            self,
+            None,
+            expected_holder,
+            expected_license,
+            options,
+            options.current_year(),
+        )
     }
 }
 
-pub fn check_file(path: &Path, expected_holder: &str, expected_license: &str) -> cu::Result<()> {
-    let format = Format::from_path(path);
-    let reader = cu::fs::reader(path)?;
-    let mut lines = reader.lines();
+/// Read the lines like [`BufRead::lines`]. If the limit is reached in the middle
+/// of a line, that line is dropped instead of being read partially
+fn capped_lines(
+    mut reader: std::io::Take<impl BufRead>,
+) -> impl Iterator<Item = std::io::Result<String>> {
+    std::iter::from_fn(move || {
+        let mut buf = Vec::new();
+        match reader.read_until(b'\n', &mut buf) {
+            Err(e) => return Some(Err(e)),
+            Ok(0) => return None,
+            Ok(_) => {}
+        }
+        if buf.last() == Some(&b'\n') {
+            buf.pop();
+            if buf.last() == Some(&b'\r') {
+                buf.pop();
+            }
+        } else if reader.limit() == 0 {
+            return None;
+        }
+        let line = String::from_utf8(buf)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e));
+        Some(line)
+    })
+}
+
+/// Check the lines of a file in `format`. `path` is only used in error messages
+fn check_lines(
+    lines: impl Iterator<Item = std::io::Result<String>>,
+    format: Format,
+    path: &Path,
+    expected_holder: &Holders,
+    expected_license: &str,
+    options: &NoticeOptions,
+) -> cu::Result<()> {
+    // stop reading once the notice is validated or definitively missing,
+    // so huge files are never read entirely
+    let max_scan_lines = options.max_scan_lines.unwrap_or(DEFAULT_MAX_SCAN_LINES);
+    // the BOM is not part of the first line
+    let mut lines = lines
+        .take(max_scan_lines)
+        .enumerate()
+        .map(|(i, line)| match line {
+            Ok(line) if i == 0 => Ok(strip_bom(line)),
+            line => line,
+        })
+        .peekable();
+
+    if let Some(Ok(line)) = lines.peek()
+        && format.is_ignore_marker(line.trim_end_matches('\r'))
+    {
+        return Ok(());
+    }
+
+    // blank lines in the preamble are only skipped if there are more preamble lines after them
+    let declarations = options.declarations_for(path);
+    let mut declaration_lines = 0;
+    let mut preamble_lines = 0;
+    let mut blank_lines = 0;
+    while let Some(Ok(line)) = lines.peek() {
+        let line = line.trim_end_matches('\r');
+        if preamble_lines > 0 && line.trim().is_empty() {
+            blank_lines += 1;
+        } else if declarations.is_some_and(|d| declaration_lines < d.max_lines && d.matches(line)) {
+            declaration_lines += 1;
+            blank_lines = 0;
+        } else if options.is_preamble(line, format, preamble_lines == 0) {
+            blank_lines = 0;
+        } else {
+            break;
+        }
+        preamble_lines += 1;
+        lines.next();
+    }
+    let mut lines = (0..blank_lines).map(|_| Ok(String::new())).chain(lines);
+
+    let header = options.full_header(expected_holder, expected_license);
+    match &header {
+        Some(header) => {
+            check_header(&mut lines, header, format, path, options)?;
+        }
+        None => check_notice(
+            &mut lines,
+            expected_holder,
+            expected_license,
+            format,
+            path,
+            options,
+        )?,
+    }
+
+    // an identical notice right after the notice is removed when fixing
+    let mut next_lines = Vec::new();
+    if header.is_none() {
+        let notice_len = options.tags_before_license.len()
+            + options.tags_after_license.len()
+            + expected_holder.len()
+            + 1;
+        let mut non_blank_lines = 0;
+        while non_blank_lines < notice_len {
+            let Some(line) = lines.next() else {
+                break;
+            };
+            let line = cu::check!(line, "error while reading file '{}'", path.display())?;
+            if !line.trim().is_empty() {
+                non_blank_lines += 1;
+            }
+            next_lines.push(line);
+        }
+        let is_duplicate = is_duplicate_notice(
+            &next_lines,
+            expected_holder,
+            expected_license,
+            format,
+            path,
+            options,
+        );
+        if is_duplicate {
+            return Err(Issue::new(IssueKind::Other, "duplicate license notice.").into());
+        }
+    }
+    let lines = next_lines.into_iter().map(Ok).chain(lines);
+
+    if let Some(max) = options.max_blank_lines_after {
+        let mut blank_lines = 0;
+        for line in lines {
+            let line = cu::check!(line, "error while reading file '{}'", path.display())?;
+            if !line.trim().is_empty() {
+                break;
+            }
+            blank_lines += 1;
+            if blank_lines > max {
+                return Err(Issue::new(
+                    IssueKind::Other,
+                    format!(
+                        "too many blank lines after the license notice, expected at most {max}."
+                    ),
+                )
+                .into());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Check the next lines are the SPDX notice
+fn check_notice(
+    lines: &mut impl Iterator<Item = std::io::Result<String>>,
+    expected_holder: &Holders,
+    expected_license: &str,
+    format: Format,
+    path: &Path,
+    options: &NoticeOptions,
+) -> cu::Result<()> {
+    check_tag_lines(lines, &options.tags_before_license, format, path)?;
 
-    let line = cu::check!(lines.next(), "missing license notice line.")?;
+    let Some(line) = lines.next() else {
+        return Err(Issue::new(IssueKind::Missing, "missing license notice line.").into());
+    };
     let line = cu::check!(line, "error while reading file '{}'", path.display())?;
+    // stray \r could be left if the line endings are partially converted
+    let line = line.trim_end_matches('\r');
+    // fixing never writes whitespace around the license
+    let expected_license = expected_license.trim();
+
+    let Some(actual_license) = format.check_strip_license_line(line) else {
+        // the notice has a copyright line for each holder after the license line
+        let next_lines = lines
+            .take(expected_holder.len())
+            .map(|x| x.unwrap_or_default())
+            .collect::<Vec<_>>();
+        let mut notice_lines = vec![line];
+        notice_lines.extend(next_lines.iter().map(|x| x.trim_end_matches('\r')));
+        if is_mixed_style_notice(&notice_lines, format) {
+            return Err(
+                Issue::new(IssueKind::Other, "inconsistent comment styles in notice.").into(),
+            );
+        }
+        return Err(Issue::new(IssueKind::Missing, "missing license notice line.").into());
+    };
+    if !format.is_canonical_license_line(line) {
+        return Err(Issue::new(IssueKind::Other, "license line has irregular spacing.").into());
+    }
+    if !options.license_matches(actual_license, expected_license) {
+        return Err(Issue::new(
+            IssueKind::WrongLicense,
+            format!("license is wrong: expected '{expected_license}', found '{actual_license}'."),
+        )
+        .with_values(expected_license, actual_license)
+        .into());
+    }
 
-    let actual_license = format.check_strip_license_line(&line);
-    let actual_license = cu::check!(actual_license, "missing license notice line.")?;
-    if actual_license != expected_license {
-        cu::bail!("license is wrong: expected '{expected_license}', found '{actual_license}'.");
+    check_tag_lines(lines, &options.tags_after_license, format, path)?;
+
+    // one copyright line for each holder, in order
+    for expected_holder in expected_holder.iter() {
+        check_copyright_line(lines, expected_holder, format, path, options)?;
+    }
+    Ok(())
+}
+
+/// Check if `lines` start with another notice for the same holder and license,
+/// after any blank lines. The years are not compared
+fn is_duplicate_notice(
+    lines: &[String],
+    expected_holder: &Holders,
+    expected_license: &str,
+    format: Format,
+    path: &Path,
+    options: &NoticeOptions,
+) -> bool {
+    let has_license_line = lines.iter().any(|line| {
+        format
+            .check_strip_license_line(line.trim_end_matches('\r'))
+            .is_some()
+    });
+    if !has_license_line {
+        return false;
     }
+    let options = NoticeOptions {
+        allow_stale_year: true,
+        ..options.clone()
+    };
+    let mut lines = lines
+        .iter()
+        .skip_while(|line| line.trim().is_empty())
+        .map(|line| Ok(line.clone()));
+    check_notice(
+        &mut lines,
+        expected_holder,
+        expected_license,
+        format,
+        path,
+        &options,
+    )
+    .is_ok()
+}
 
-    let line = cu::check!(lines.next(), "missing copyright line.")?;
+/// Check the next line is the copyright line for `expected_holder`
+fn check_copyright_line(
+    lines: &mut impl Iterator<Item = std::io::Result<String>>,
+    expected_holder: &str,
+    format: Format,
+    path: &Path,
+    options: &NoticeOptions,
+) -> cu::Result<()> {
+    let Some(line) = lines.next() else {
+        return Err(Issue::new(IssueKind::Missing, "missing copyright line.").into());
+    };
     let line = cu::check!(line, "error while reading file '{}'", path.display())?;
+    let line = line.trim_end_matches('\r');
+
+    let Some(copyright_info) = format.check_strip_copyright_line(line) else {
+        if find_copyright_format(line).is_some() {
+            return Err(
+                Issue::new(IssueKind::Other, "inconsistent comment styles in notice.").into(),
+            );
+        }
+        return Err(Issue::new(IssueKind::Missing, "missing copyright line.").into());
+    };
+    let style = options.copyright_style;
+    if !format.is_canonical_copyright_line(line, style) {
+        let any_style = CopyrightStyle::all()
+            .iter()
+            .any(|s| format.is_canonical_copyright_line(line, *s));
+        if !any_style {
+            return Err(
+                Issue::new(IssueKind::Other, "copyright line has irregular spacing.").into(),
+            );
+        }
+        if options.exact_copyright_style {
+            return Err(Issue::new(
+                IssueKind::Other,
+                format!("copyright line should use 'Copyright {}'.", style.mark()),
+            )
+            .into());
+        }
+    }
+
+    let (year_start, year_end, actual_holder, actual_email) =
+        parse_copyright_info(copyright_info, options.current_year());
+    let years = copyright_info.split(' ').next().unwrap_or_default();
+    let (_, range_end) = parse_year_range(years, options.current_year());
+    if range_end < year_start {
+        return Err(Issue::new(
+            IssueKind::WrongYear,
+            format!("copyright range {year_start}-{range_end} is inverted."),
+        )
+        .into());
+    }
+    if options.is_year_too_old(year_start) {
+        let min_year = options.min_year.unwrap_or_default();
+        return Err(Issue::new(
+            IssueKind::WrongYear,
+            format!("copyright start year {year_start} is before the minimum year {min_year}."),
+        )
+        .into());
+    }
+    // the email is only checked if it's in the config
+    let (expected_holder, expected_email) = split_email(expected_holder);
+    if !options.holder_matches(actual_holder, expected_holder) {
+        return Err(Issue::new(
+            IssueKind::WrongHolder,
+            format!("holder is wrong: expected '{expected_holder}', found '{actual_holder}'."),
+        )
+        .with_values(expected_holder, actual_holder)
+        .into());
+    }
+    if let Some(expected_email) = expected_email {
+        match actual_email {
+            None => {
+                return Err(Issue::new(
+                    IssueKind::WrongHolder,
+                    format!("email is missing: expected '<{expected_email}>'."),
+                )
+                .into());
+            }
+            Some(actual_email) if actual_email != expected_email => {
+                return Err(Issue::new(
+                    IssueKind::WrongHolder,
+                    format!(
+                        "email is wrong: expected '<{expected_email}>', found '<{actual_email}>'."
+                    ),
+                )
+                .with_values(expected_email, actual_email)
+                .into());
+            }
+            Some(_) => {}
+        }
+    }
+    check_year_end(year_end, options)
+}
+
+/// Check the next lines are the full header
+fn check_header(
+    lines: &mut impl Iterator<Item = std::io::Result<String>>,
+    header: &Header,
+    format: Format,
+    path: &Path,
+    options: &NoticeOptions,
+) -> cu::Result<()> {
+    let mut years = None;
+    for i in 0..header.lines.len() {
+        let Some(line) = lines.next() else {
+            let message = format!("missing header line {}.", i + 1);
+            return Err(Issue::new(IssueKind::Missing, message).into());
+        };
+        let line = cu::check!(line, "error while reading file '{}'", path.display())?;
+        let line = line.trim_end_matches('\r');
+        match header.match_line(i, format, line, options.current_year()) {
+            None => {
+                let current_year = options.current_year();
+                let expected = header.format_line(i, format, current_year, current_year);
+                let message = format!(
+                    "header line {} is wrong: expected '{expected}', found '{line}'.",
+                    i + 1
+                );
+                return Err(Issue::new(IssueKind::Other, message)
+                    .with_values(&expected, line)
+                    .into());
+            }
+            Some(y) => {
+                years = years.or(y);
+            }
+        }
+    }
+    let Some((year_start, year_end)) = years else {
+        return Ok(());
+    };
+    if options.is_year_too_old(year_start) {
+        let min_year = options.min_year.unwrap_or_default();
+        return Err(Issue::new(
+            IssueKind::WrongYear,
+            format!("copyright start year {year_start} is before the minimum year {min_year}."),
+        )
+        .into());
+    }
+    check_year_end(year_end, options)
+}
+
+/// Check the copyright info ends at the current year,
+/// or a past year if allowed by the options
+fn check_year_end(year_end: u32, options: &NoticeOptions) -> cu::Result<()> {
+    let current_year = options.current_year();
+    let allowed = options.allow_stale_year && year_end < current_year;
+    if year_end != current_year && !allowed {
+        return Err(StaleYear {
+            year_end,
+            current_year,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Full header template with the holder and license substituted
+struct Header {
+    lines: Vec<String>,
+}
 
-    let copyright_info = format.check_strip_copyright_line(&line);
-    let copyright_info = cu::check!(copyright_info, "missing copyright line.")?;
+impl Header {
+    fn new(template: &str, holder: &str, license: &str) -> Self {
+        let lines = template
+            .trim_end()
+            .lines()
+            .map(|line| {
+                line.trim_end()
+                    .replace("{holder}", holder.trim())
+                    .replace("{license}", license.trim())
+            })
+            .collect();
+        Self { lines }
+    }
+
+    /// Format the line at `i` as a comment, with the years from `year_start`
+    /// to `year_end`
+    fn format_line(&self, i: usize, format: Format, year_start: u32, year_end: u32) -> String {
+        let years = if year_start == year_end {
+            year_start.to_string()
+        } else {
+            format!("{year_start}-{year_end}")
+        };
+        comment_line(format, &self.lines[i].replace("{year}", &years))
+    }
+
+    /// Match the actual line with the line at `i`. Returns `Some((year_start, year_end))`
+    /// if the line has years, or `None` if it doesn't. `present` as the end year
+    /// is `current_year`
+    fn match_line(
+        &self,
+        i: usize,
+        format: Format,
+        actual: &str,
+        current_year: u32,
+    ) -> Option<Option<(u32, u32)>> {
+        let expected = comment_line(format, &self.lines[i]);
+        let Some((before, after)) = expected.split_once("{year}") else {
+            return (actual == expected).then_some(None);
+        };
+        let years = actual.strip_prefix(before)?.strip_suffix(after)?;
+        let (year_start, year_end) = match years.split_once('-') {
+            Some((start, end)) => (
+                cu::parse::<u32>(start).ok()?,
+                parse_year_end(end, current_year)?,
+            ),
+            None => {
+                let year = cu::parse::<u32>(years).ok()?;
+                (year, year)
+            }
+        };
+        Some(Some((year_start, year_end.max(year_start))))
+    }
+}
 
-    let (_, year_end, actual_holder) = parse_copyright_info(copyright_info);
-    if actual_holder != expected_holder {
-        cu::bail!("holder is wrong: expected '{expected_holder}', found '{actual_holder}'.");
+/// Wrap the text in a comment of the format
+fn comment_line(format: Format, text: &str) -> String {
+    let text = text.trim_end();
+    if text.is_empty() {
+        return format!("{}{}", format.prefix().trim_end(), format.suffix());
     }
-    let current_year = current_year();
-    if year_end != current_year {
-        cu::bail!("copyright info ends at {year_end}, but we are in {current_year}.");
+    format!("{}{text}{}", format.prefix(), format.suffix())
+}
+
+/// Error from [`check_file`] when the only issue is the copyright year
+/// not ending at the current year, which is always safe to fix
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaleYear {
+    pub year_end: u32,
+    pub current_year: u32,
+}
+
+impl std::fmt::Display for StaleYear {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "copyright info ends at {}, but we are in {}.",
+            self.year_end, self.current_year
+        )
+    }
+}
+
+impl std::error::Error for StaleYear {}
+
+/// Kind of the issue found by [`check_file`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueKind {
+    /// The license notice (or part of it) is missing
+    Missing,
+    /// The license in the notice is not the configured one
+    WrongLicense,
+    /// The copyright holder in the notice is not the configured one
+    WrongHolder,
+    /// The copyright year does not end at the current year
+    WrongYear,
+    /// Other issues, for example inconsistent comment styles
+    Other,
+}
+
+impl IssueKind {
+    /// Get the name of the kind used in machine-readable output
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Missing => "missing",
+            Self::WrongLicense => "wrong-license",
+            Self::WrongHolder => "wrong-holder",
+            Self::WrongYear => "wrong-year",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// Error from [`check_file`] describing the issue with the notice
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub kind: IssueKind,
+    /// The expected value, if the issue is a wrong value
+    pub expected: Option<String>,
+    /// The value found in the file, if the issue is a wrong value
+    pub actual: Option<String>,
+    message: String,
+}
+
+impl Issue {
+    fn new(kind: IssueKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            expected: None,
+            actual: None,
+            message: message.into(),
+        }
+    }
+
+    fn with_values(mut self, expected: &str, actual: &str) -> Self {
+        self.expected = Some(expected.to_string());
+        self.actual = Some(actual.to_string());
+        self
+    }
+
+    /// Get the issue from the error returned by [`check_file`]. Errors that
+    /// are not about the notice (like failing to read the file) are [`IssueKind::Other`]
+    pub fn from_error(e: &cu::Error) -> Self {
+        if let Some(issue) = e.downcast_ref::<Issue>() {
+            return issue.clone();
+        }
+        if let Some(stale) = e.downcast_ref::<StaleYear>() {
+            return Self::new(IssueKind::WrongYear, stale.to_string())
+                .with_values(&stale.current_year.to_string(), &stale.year_end.to_string());
+        }
+        Self::new(IssueKind::Other, e.to_string())
+    }
+
+    /// Get the message describing the issue
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl std::fmt::Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Issue {}
+
+/// Check the next lines are the extra tag lines, in order
+fn check_tag_lines(
+    lines: &mut impl Iterator<Item = std::io::Result<String>>,
+    tags: &[String],
+    format: Format,
+    path: &Path,
+) -> cu::Result<()> {
+    for tag in tags {
+        let missing = || Issue::new(IssueKind::Missing, format!("missing tag line '{tag}'."));
+        let Some(line) = lines.next() else {
+            return Err(missing().into());
+        };
+        let line = cu::check!(line, "error while reading file '{}'", path.display())?;
+        let line = line.trim_end_matches('\r');
+        if format.strip_comment(line, tag) != Some("") {
+            return Err(missing().into());
+        }
     }
+    Ok(())
+}
+
+pub fn fix_file(
+    path: &Path,
+    expected_holder: &Holders,
+    expected_license: &str,
+    options: &NoticeOptions,
+) -> cu::Result<()> {
+    fix_file_with_fs(
+        &DiskFileSystem,
+        path,
+        expected_holder,
+        expected_license,
+        options,
+    )
+}
 
+/// Fix the file at `path`, accessed through `fs`
+pub fn fix_file_with_fs(
+    fs: &dyn FileSystem,
+    path: &Path,
+    expected_holder: &Holders,
+    expected_license: &str,
+    options: &NoticeOptions,
+) -> cu::Result<()> {
+    let fixed = fixed_file_content_with_fs(fs, path, expected_holder, expected_license, options)?;
+    fs.write(path, &fixed)?;
     Ok(())
 }
 
-pub fn fix_file(path: &Path, expected_holder: &str, expected_license: &str) -> cu::Result<()> {
-    let format = Format::from_path(path);
-    let file_content = cu::fs::read_string(path)?;
+/// Compute the content of the file at `path` after fixing, without writing it back
+pub fn fixed_file_content(
+    path: &Path,
+    expected_holder: &Holders,
+    expected_license: &str,
+    options: &NoticeOptions,
+) -> cu::Result<String> {
+    fixed_file_content_with_fs(
+        &DiskFileSystem,
+        path,
+        expected_holder,
+        expected_license,
+        options,
+    )
+}
+
+/// Compute the content of the file at `path`, accessed through `fs`, after fixing
+pub fn fixed_file_content_with_fs(
+    fs: &dyn FileSystem,
+    path: &Path,
+    expected_holder: &Holders,
+    expected_license: &str,
+    options: &NoticeOptions,
+) -> cu::Result<String> {
+    fixed_file_content_with_year(fs, path, expected_holder, expected_license, options, None)
+}
+
+/// Compute the content of the file at `path`, accessed through `fs`, after fixing.
+///
+/// If the file has no existing notice, `new_year` is used as the start year
+/// of the new notice, instead of the current year
+pub fn fixed_file_content_with_year(
+    fs: &dyn FileSystem,
+    path: &Path,
+    expected_holder: &Holders,
+    expected_license: &str,
+    options: &NoticeOptions,
+    new_year: Option<u32>,
+) -> cu::Result<String> {
+    let file_content = fs.read_string(path)?;
+    let format = options.format_for_content(path, first_line(&file_content));
+    fixed_content(
+        &file_content,
+        format,
+        Some(path),
+        expected_holder,
+        expected_license,
+        options,
+        new_year.unwrap_or_else(|| options.current_year()),
+    )
+}
+
+/// Compute the content of the file at `path` after removing the license notice,
+/// without writing it back. Returns `None` if there's no notice to remove
+pub fn removed_file_content(path: &Path, options: &NoticeOptions) -> cu::Result<Option<String>> {
+    removed_file_content_with_fs(&DiskFileSystem, path, options)
+}
+
+/// Compute the content of the file at `path`, accessed through `fs`, after removing
+/// the license notice. Returns `None` if there's no notice to remove
+pub fn removed_file_content_with_fs(
+    fs: &dyn FileSystem,
+    path: &Path,
+    options: &NoticeOptions,
+) -> cu::Result<Option<String>> {
+    let file_content = fs.read_string(path)?;
+    let format = options.format_for_content(path, first_line(&file_content));
+    Ok(removed_content(
+        &file_content,
+        format,
+        is_markdown(path),
+        options,
+    ))
+}
+
+/// Remove the license line, the copyright line and the extra tag lines before
+/// the sentinel, along with the blank line right after them.
+/// Returns `None` if nothing is removed
+fn removed_content(
+    file_content: &str,
+    format: Format,
+    skip_code_blocks: bool,
+    options: &NoticeOptions,
+) -> Option<String> {
+    if let Some(rest) = file_content.strip_prefix(BOM) {
+        let removed = removed_content(rest, format, skip_code_blocks, options)?;
+        return Some(format!("{BOM}{removed}"));
+    }
+    let mut out = String::with_capacity(file_content.len());
+    let mut found_license_line = false;
+    let mut found_copyright_line = false;
+    let mut removed = false;
+    let mut after_notice = false;
+    let mut in_code_block = false;
+    // notices after declarations are removed regardless
+    let preamble_len = options.preamble_len(file_content, format, None);
+    let mut lines = file_content.split_inclusive('\n').enumerate();
+    for (i, raw_line) in lines.by_ref() {
+        let line = raw_line.trim_end_matches(['\n', '\r']);
+        let is_after_notice = std::mem::take(&mut after_notice);
+        if format.starts_with_sentinel(line) {
+            out.push_str(raw_line);
+            break;
+        }
+        if i < preamble_len {
+            out.push_str(raw_line);
+            continue;
+        }
+        if skip_code_blocks && (in_code_block || is_code_fence(line)) {
+            if is_code_fence(line) {
+                in_code_block = !in_code_block;
+            }
+            out.push_str(raw_line);
+            continue;
+        }
+        let is_license_line =
+            !found_license_line && format.check_strip_license_line(line).is_some();
+        let is_copyright_line = !is_license_line
+            && !found_copyright_line
+            && format.check_strip_copyright_line(line).is_some();
+        found_license_line |= is_license_line;
+        found_copyright_line |= is_copyright_line;
+        if is_license_line || is_copyright_line || options.is_any_tag_line(line, format) {
+            removed = true;
+            after_notice = true;
+            continue;
+        }
+        if is_after_notice && line.trim().is_empty() {
+            continue;
+        }
+        out.push_str(raw_line);
+    }
+    if !removed {
+        return None;
+    }
+    // keep everything after the sentinel
+    for (_, raw_line) in lines {
+        out.push_str(raw_line);
+    }
+    Some(out)
+}
+
+/// Compute the fixed content of a file in `format`. If `path` is known,
+/// its extension is used for the options that depend on the file type.
+/// `new_year` is the start year if a new notice is added
+fn fixed_content(
+    file_content: &str,
+    format: Format,
+    path: Option<&Path>,
+    expected_holder: &Holders,
+    expected_license: &str,
+    options: &NoticeOptions,
+    new_year: u32,
+) -> cu::Result<String> {
+    if let Some(rest) = file_content.strip_prefix(BOM) {
+        // the BOM stays at the very start, before the notice
+        let fixed = fixed_content(
+            rest,
+            format,
+            path,
+            expected_holder,
+            expected_license,
+            options,
+            new_year,
+        )?;
+        return Ok(format!("{BOM}{fixed}"));
+    }
+    let first_line = file_content.lines().next().unwrap_or_default();
+    if format.is_ignore_marker(first_line.trim_end_matches('\r')) {
+        return Ok(file_content.to_string());
+    }
     let lines = file_content.lines();
-    let mut buf = FixBuf::default();
-    // usually this should only go through the first line
-    // unless the file is unconventional
-    if file_content.contains("\r\n") {
-        cu::debug!("will use CRLF for file '{}'", path.display());
+    let mut buf = FixBuf {
+        blank_after: path.is_none_or(|path| options.blank_after_for(path)),
+        no_trailing_newline: !file_content.is_empty() && !file_content.ends_with('\n'),
+        ..Default::default()
+    };
+    if is_crlf_dominant(file_content) {
+        if let Some(path) = path {
+            cu::debug!("will use CRLF for file '{}'", path.display());
+        }
         buf.set_crlf(true);
     }
 
+    if let Some(header) = options.full_header(expected_holder, expected_license) {
+        return fixed_content_with_header(
+            file_content,
+            &header,
+            format,
+            path,
+            buf,
+            options,
+            new_year,
+        );
+    }
+
+    let preamble_len = options.preamble_len(file_content, format, path);
+    // a notice at the top in mixed or wrong comment styles is normalized to `format`.
+    // The notice has the license line and a copyright line for each holder
+    let notice_len = 1 + expected_holder.len();
+    let first_lines = file_content
+        .lines()
+        .skip(preamble_len)
+        .take(notice_len)
+        .map(|x| x.trim_end_matches('\r'))
+        .collect::<Vec<_>>();
+    let foreign_lines =
+        if first_lines.len() == notice_len && is_foreign_style_notice(&first_lines, format) {
+            preamble_len..preamble_len + notice_len
+        } else {
+            0..0
+        };
+
     let mut found_license_line = false;
-    let mut found_copyright_line = false;
+    let holder_count = expected_holder.len();
+    let mut found_copyright_lines = 0;
+    // an identical notice right after the notice is removed
+    let mut content_after_notice = false;
+    let mut duplicate_copyright_lines = 0;
+    let mut after_duplicate = false;
     let mut found_sentinel = false;
+    // example notices in code blocks should not be treated as notices
+    let skip_code_blocks = path.is_some_and(is_markdown);
+    let mut in_code_block = false;
+    // number of blank lines that can still be kept, if collapsing blank lines
+    let mut blank_lines_left = options.max_blank_lines_after;
 
-    for line in lines {
+    for (i, line) in lines.enumerate() {
         if found_sentinel {
             buf.push_line(line, format);
             continue;
         }
+        if i < preamble_len {
+            buf.push_preamble(line);
+            continue;
+        }
         if format.starts_with_sentinel(line) {
             found_sentinel = true;
             buf.push_line(line, format);
             continue;
         }
-        if format.check_strip_license_line(line).is_some() {
+        if skip_code_blocks && (in_code_block || is_code_fence(line)) {
+            if is_code_fence(line) {
+                in_code_block = !in_code_block;
+            }
+            blank_lines_left = None;
+            buf.push_line(line, format);
+            continue;
+        }
+        // notice lines with stray \r are rewritten without it
+        let trimmed_line = line.trim_end_matches('\r');
+        let line_format = if foreign_lines.contains(&i) {
+            foreign_line_format(trimmed_line, format)
+        } else {
+            format
+        };
+        if let Some(actual_license) = line_format.check_strip_license_line(trimmed_line) {
             if found_license_line {
+                let is_duplicate = found_copyright_lines == holder_count
+                    && !content_after_notice
+                    && duplicate_copyright_lines == 0
+                    && options.license_matches(actual_license, expected_license.trim());
+                if is_duplicate {
+                    duplicate_copyright_lines = holder_count;
+                    continue;
+                }
                 cu::bail!(
                     "multiple license line found! Consider adding a sentinel line if there are other license notices that need to be kept!"
                 );
             }
             found_license_line = true;
+            blank_lines_left = options.max_blank_lines_after;
             continue;
         }
-        if let Some(copyright_info) = format.check_strip_copyright_line(line) {
-            if found_copyright_line {
+        // tag lines are removed and added back in the right order
+        if options.is_any_tag_line(trimmed_line, format) {
+            blank_lines_left = options.max_blank_lines_after;
+            continue;
+        }
+        if let Some(copyright_info) = line_format.check_strip_copyright_line(trimmed_line) {
+            if duplicate_copyright_lines > 0 {
+                let (_, _, actual_holder, _) =
+                    parse_copyright_info(copyright_info, options.current_year());
+                let expected = expected_holder
+                    .iter()
+                    .nth(holder_count - duplicate_copyright_lines)
+                    .unwrap_or_default();
+                if !options.holder_matches(actual_holder, split_email(expected).0) {
+                    cu::bail!(
+                        "multiple copyright line found! Consider adding a sentinel line if there are other license notices that need to be kept!"
+                    );
+                }
+                duplicate_copyright_lines -= 1;
+                // the notice already has blank lines after it
+                after_duplicate = duplicate_copyright_lines == 0;
+                continue;
+            }
+            if found_copyright_lines == holder_count {
                 cu::bail!(
                     "multiple copyright line found! Consider adding a sentinel line if there are other license notices that need to be kept!"
                 );
             }
-            found_copyright_line = true;
-            let (year_start, _, _) = parse_copyright_info(copyright_info);
-            if year_start > current_year() {
+            found_copyright_lines += 1;
+            let (year_start, _, _, actual_email) =
+                parse_copyright_info(copyright_info, options.current_year());
+            if year_start > options.current_year() {
                 cu::bail!("copyright start year is in the future! Manual fix required.");
             }
-            buf.perform_fix_if_need(format, year_start, expected_holder, expected_license)?;
+            if options.is_year_too_old(year_start) {
+                cu::bail!("copyright start year is too old! Manual fix required.");
+            }
+            let holders = if holder_count > 1 {
+                existing_holders(
+                    file_content,
+                    format,
+                    expected_holder,
+                    skip_code_blocks,
+                    foreign_lines.clone(),
+                    options,
+                    new_year,
+                )?
+            } else {
+                // keep the email in the file if the config doesn't have one
+                let expected = expected_holder.iter().next().unwrap_or_default();
+                let holder = match (split_email(expected).1, actual_email) {
+                    (None, Some(email)) => format!("{} <{email}>", expected.trim()),
+                    _ => expected.to_string(),
+                };
+                vec![(year_start, holder)]
+            };
+            buf.perform_fix_if_need(format, &holders, expected_license, options)?;
+            blank_lines_left = options.max_blank_lines_after;
+            continue;
+        }
+        if duplicate_copyright_lines > 0 {
+            cu::bail!(
+                "multiple license line found! Consider adding a sentinel line if there are other license notices that need to be kept!"
+            );
+        }
+        if !line.trim().is_empty() {
+            content_after_notice = found_copyright_lines == holder_count;
+            after_duplicate = false;
+        } else if after_duplicate {
             continue;
         }
+        if let Some(left) = blank_lines_left {
+            if !line.trim().is_empty() {
+                blank_lines_left = None;
+            } else if left == 0 {
+                continue;
+            } else {
+                blank_lines_left = Some(left - 1);
+            }
+        }
         buf.push_line(line, format);
     }
+    if duplicate_copyright_lines > 0 {
+        cu::bail!(
+            "multiple license line found! Consider adding a sentinel line if there are other license notices that need to be kept!"
+        );
+    }
     // format new notice if didn't find one
-    buf.perform_fix_if_need(format, current_year(), expected_holder, expected_license)?;
+    let holders = expected_holder
+        .iter()
+        .map(|holder| (new_year, holder.to_string()))
+        .collect::<Vec<_>>();
+    buf.perform_fix_if_need(format, &holders, expected_license, options)?;
 
-    cu::fs::write(path, buf.buf)?;
-    Ok(())
+    Ok(buf.finish())
+}
+
+/// Get the start year and the holder for each of the multiple holders in `expected_holder`,
+/// from the existing copyright lines in the file. Holders without a copyright line
+/// use `new_year`. Error if a copyright line is for a holder not expected.
+///
+/// Lines at indices in `foreign_lines` are a notice in another comment style,
+/// see [`is_foreign_style_notice`]
+fn existing_holders(
+    file_content: &str,
+    format: Format,
+    expected_holder: &Holders,
+    skip_code_blocks: bool,
+    foreign_lines: std::ops::Range<usize>,
+    options: &NoticeOptions,
+    new_year: u32,
+) -> cu::Result<Vec<(u32, String)>> {
+    let mut holders = expected_holder
+        .iter()
+        .map(|holder| (new_year, holder.to_string()))
+        .collect::<Vec<_>>();
+    let mut in_code_block = false;
+    for (i, line) in file_content.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if format.starts_with_sentinel(line) {
+            break;
+        }
+        if skip_code_blocks && (in_code_block || is_code_fence(line)) {
+            if is_code_fence(line) {
+                in_code_block = !in_code_block;
+            }
+            continue;
+        }
+        let line_format = if foreign_lines.contains(&i) {
+            foreign_line_format(line, format)
+        } else {
+            format
+        };
+        let Some(copyright_info) = line_format.check_strip_copyright_line(line) else {
+            continue;
+        };
+        let (year_start, _, actual_holder, actual_email) =
+            parse_copyright_info(copyright_info, options.current_year());
+        let found = expected_holder
+            .iter()
+            .position(|holder| options.holder_matches(actual_holder, split_email(holder).0));
+        let Some(i) = found else {
+            cu::bail!(
+                "copyright line for unexpected holder '{actual_holder}' found! Consider adding a sentinel line if there are other license notices that need to be kept!"
+            );
+        };
+        holders[i].0 = year_start;
+        // keep the email in the file if the config doesn't have one
+        if let (None, Some(email)) = (split_email(&holders[i].1).1, actual_email) {
+            holders[i].1 = format!("{} <{email}>", holders[i].1);
+        }
+    }
+    Ok(holders)
 }
 
 #[derive(Default)]
 struct FixBuf {
+    /// Lines that must stay before the notice
+    preamble: String,
     buf: String,
     is_crlf: bool,
+    /// If a blank line should be inserted after the notice
+    blank_after: bool,
+    /// If the original file doesn't end with a line ending, which should be kept
+    no_trailing_newline: bool,
     fixed: bool,
     fixed_when_empty: bool,
 }
@@ -207,6 +1989,13 @@ impl FixBuf {
     }
     fn push_line(&mut self, line: &str, format: Format) {
         let le_byte_len = if self.is_crlf { 2 } else { 1 };
+        // blank lines that separated the old notice from the content are kept,
+        // with a whitespace-only line becoming the bare separator
+        let line = if self.fixed_when_empty && line.trim().is_empty() {
+            ""
+        } else {
+            line
+        };
         if self.fixed_when_empty {
             if !format.starts_with_sentinel(line) && !line.is_empty() {
                 self.buf.reserve(line.len() + le_byte_len * 2);
@@ -222,20 +2011,42 @@ impl FixBuf {
         self.buf.push_str(line);
         self.push_line_ending();
     }
+    /// Insert the notice with a copyright line for each (start year, holder),
+    /// if not inserted yet
     fn perform_fix_if_need(
         &mut self,
         format: Format,
-        year_start: u32,
-        holder: &str,
+        holders: &[(u32, String)],
         license: &str,
+        options: &NoticeOptions,
     ) -> cu::Result<()> {
         if self.fixed {
             return Ok(());
         }
-        let current_content = std::mem::take(&mut self.buf);
-        format.format(year_start, holder, license, self.is_crlf, &mut self.buf)?;
+        let holders = holders
+            .iter()
+            .map(|(year_start, holder)| (*year_start, holder.as_str()))
+            .collect::<Vec<_>>();
+        let mut notice = String::new();
+        format.format_with_holders(&holders, license, options, self.is_crlf, &mut notice)?;
+        self.insert_notice(&notice, format);
+        Ok(())
+    }
+    /// Insert the formatted notice before the current content
+    fn insert_notice(&mut self, notice: &str, format: Format) {
+        let mut current_content = std::mem::take(&mut self.buf);
+        // a whitespace-only line right after the notice becomes the bare separator
+        if let Some(end) = current_content.find('\n') {
+            let first_line = current_content[..end].trim_end_matches('\r');
+            if !first_line.is_empty() && first_line.trim().is_empty() {
+                current_content.replace_range(..first_line.len(), "");
+            }
+        }
+        self.buf.push_str(notice);
         // add an empty line if needed
-        if !current_content.is_empty() {
+        if !self.blank_after {
+            // not inserting the empty line
+        } else if !current_content.is_empty() {
             if !format.starts_with_sentinel(&current_content) && !current_content.starts_with('\n')
             {
                 self.push_line_ending();
@@ -245,7 +2056,22 @@ impl FixBuf {
         }
         self.buf.push_str(&current_content);
         self.fixed = true;
-        Ok(())
+    }
+    fn push_preamble(&mut self, line: &str) {
+        self.preamble.push_str(line);
+        self.preamble
+            .push_str(if self.is_crlf { "\r\n" } else { "\n" });
+    }
+    fn finish(mut self) -> String {
+        self.preamble.push_str(&self.buf);
+        let mut out = self.preamble;
+        if self.no_trailing_newline {
+            let le_byte_len = if out.ends_with("\r\n") { 2 } else { 1 };
+            if out.ends_with('\n') {
+                out.truncate(out.len() - le_byte_len);
+            }
+        }
+        out
     }
     fn push_line_ending(&mut self) {
         if self.is_crlf {
@@ -256,25 +2082,330 @@ impl FixBuf {
     }
 }
 
-fn parse_copyright_info(info: &str) -> (u32, u32, &str) {
-    let mut parts = info.splitn(2, ' ');
-    let (year_start, year_end) = match parts.next() {
-        None => (DEFAULT_YEAR, DEFAULT_YEAR),
-        Some(x) => {
-            let mut parts = x.splitn(2, '-');
-            let year_start = parts
-                .next()
-                .and_then(|x| cu::parse::<u32>(x).ok())
-                .unwrap_or(DEFAULT_YEAR);
-            let year_end = parts
-                .next()
-                .and_then(|x| cu::parse::<u32>(x).ok())
-                .unwrap_or(year_start);
-            (year_start, year_end.max(year_start))
+/// Compute the fixed content when a full header is used instead of the SPDX notice.
+///
+/// The header is expected at the top of the file (after the preamble). An SPDX notice
+/// at the top is replaced by the header, keeping the start year.
+fn fixed_content_with_header(
+    file_content: &str,
+    header: &Header,
+    format: Format,
+    path: Option<&Path>,
+    mut buf: FixBuf,
+    options: &NoticeOptions,
+    new_year: u32,
+) -> cu::Result<String> {
+    let mut lines = file_content.lines();
+    for line in lines
+        .by_ref()
+        .take(options.preamble_len(file_content, format, path))
+    {
+        buf.push_preamble(line);
+    }
+    let rest = lines.collect::<Vec<_>>();
+    let current_year = options.current_year();
+    let (year_start, skip) = find_existing_header(&rest, header, format, new_year, current_year);
+    if year_start > current_year {
+        cu::bail!("copyright start year is in the future! Manual fix required.");
+    }
+    if options.is_year_too_old(year_start) {
+        cu::bail!("copyright start year is too old! Manual fix required.");
+    }
+
+    let le = if buf.is_crlf { "\r\n" } else { "\n" };
+    let mut notice = String::new();
+    for i in 0..header.lines.len() {
+        notice.push_str(&header.format_line(i, format, year_start, current_year));
+        notice.push_str(le);
+    }
+    buf.insert_notice(&notice, format);
+
+    let mut found_sentinel = false;
+    for line in &rest[skip..] {
+        if !found_sentinel {
+            if format.starts_with_sentinel(line) {
+                found_sentinel = true;
+            } else if header
+                .match_line(0, format, line.trim_end_matches('\r'), current_year)
+                .is_some()
+            {
+                cu::bail!(
+                    "multiple headers found! Consider adding a sentinel line if there are other license notices that need to be kept!"
+                );
+            }
+        }
+        buf.push_line(line, format);
+    }
+    Ok(buf.finish())
+}
+
+/// Find the existing header (or SPDX notice) at the start of `lines`.
+/// Returns the start year and the number of lines it takes. If there's no existing
+/// header, `new_year` is returned as the start year
+fn find_existing_header(
+    lines: &[&str],
+    header: &Header,
+    format: Format,
+    new_year: u32,
+    current_year: u32,
+) -> (u32, usize) {
+    let n = header.lines.len();
+    if lines.len() >= n {
+        let mut years = None;
+        let mut matched = true;
+        for (i, line) in lines[..n].iter().enumerate() {
+            match header.match_line(i, format, line.trim_end_matches('\r'), current_year) {
+                None => {
+                    matched = false;
+                    break;
+                }
+                Some(y) => {
+                    years = years.or(y);
+                }
+            }
         }
+        if matched {
+            let year_start = years.map(|(start, _)| start).unwrap_or(current_year);
+            return (year_start, n);
+        }
+    }
+    // migrating from the SPDX notice
+    if let [license_line, copyright_line, ..] = lines
+        && format
+            .check_strip_license_line(license_line.trim_end_matches('\r'))
+            .is_some()
+        && let Some(info) = format.check_strip_copyright_line(copyright_line.trim_end_matches('\r'))
+    {
+        let (year_start, _, _, _) = parse_copyright_info(info, current_year);
+        return (year_start, 2);
+    }
+    (new_year, 0)
+}
+
+/// Check if most lines in the content end with CRLF. If it's a tie,
+/// the ending of the first line is used
+fn is_crlf_dominant(content: &str) -> bool {
+    let crlf = content.matches("\r\n").count();
+    let lf = content.matches('\n').count() - crlf;
+    match crlf.cmp(&lf) {
+        std::cmp::Ordering::Greater => true,
+        std::cmp::Ordering::Less => false,
+        std::cmp::Ordering::Equal => content
+            .find('\n')
+            .is_some_and(|i| content[..i].ends_with('\r')),
+    }
+}
+
+/// Check if the line must stay as the first line of the file, before the notice,
+/// like a shebang or a declaration (see [`Format::is_declaration_line`])
+fn is_preamble_line(line: &str, format: Format) -> bool {
+    // `#![...]` is an inner attribute in Rust, not a shebang
+    let is_shebang = line.starts_with("#!") && !line.starts_with("#![");
+    is_shebang || format.is_declaration_line(line)
+}
+
+/// Find the format in which the line is a license line
+fn find_license_format(line: &str) -> Option<Format> {
+    supported_formats()
+        .iter()
+        .copied()
+        .find(|f| f.check_strip_license_line(line).is_some())
+}
+
+/// Find the format in which the line is a copyright line
+fn find_copyright_format(line: &str) -> Option<Format> {
+    supported_formats()
+        .iter()
+        .copied()
+        .find(|f| f.check_strip_copyright_line(line).is_some())
+}
+
+/// Get the format of each line in `lines`, if they are a license line followed by
+/// copyright lines, in any comment style. `format` is preferred if a line is in it
+fn notice_line_formats(lines: &[&str], format: Format) -> Option<Vec<Format>> {
+    let (license_line, copyright_lines) = lines.split_first()?;
+    let license_format = if format.check_strip_license_line(license_line).is_some() {
+        format
+    } else {
+        find_license_format(license_line)?
+    };
+    let mut formats = vec![license_format];
+    for line in copyright_lines {
+        let copyright_format = if format.check_strip_copyright_line(line).is_some() {
+            format
+        } else {
+            find_copyright_format(line)?
+        };
+        formats.push(copyright_format);
+    }
+    Some(formats)
+}
+
+/// Check if `lines` are a license line followed by copyright lines
+/// in different comment styles, with at least one of them in `format`
+fn is_mixed_style_notice(lines: &[&str], format: Format) -> bool {
+    let Some(formats) = notice_line_formats(lines, format) else {
+        return false;
+    };
+    formats.iter().any(|f| *f != formats[0]) && formats.contains(&format)
+}
+
+/// Check if `lines` are a license line followed by copyright lines
+/// with at least one of them not in `format`, for example a `//` notice in a `#` file
+fn is_foreign_style_notice(lines: &[&str], format: Format) -> bool {
+    notice_line_formats(lines, format).is_some_and(|formats| formats.iter().any(|f| *f != format))
+}
+
+/// Get the format of a line in a notice found by [`is_foreign_style_notice`]
+fn foreign_line_format(line: &str, format: Format) -> Format {
+    find_license_format(line)
+        .or_else(|| find_copyright_format(line))
+        .unwrap_or(format)
+}
+
+fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .and_then(|x| x.to_str())
+        .is_some_and(|ext| MARKDOWN_EXTENSIONS.binary_search(&ext).is_ok())
+}
+
+/// Check if the line starts or ends a fenced code block in markdown
+fn is_code_fence(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("```") || line.starts_with("~~~")
+}
+
+/// Parse the copyright info after `Copyright (c) ` into the start year, the end year,
+/// the holder, and the email of the holder if any (see [`split_email`]).
+/// `present` as the end year is `current_year`
+fn parse_copyright_info(info: &str, current_year: u32) -> (u32, u32, &str, Option<&str>) {
+    let mut parts = info.splitn(2, ' ');
+    let (year_start, year_end) = parse_year_range(parts.next().unwrap_or(""), current_year);
+    // inverted ranges are reported when checking, see check_notice
+    let year_end = year_end.max(year_start);
+    let (holder, email) = split_email(parts.next().unwrap_or(""));
+    (year_start, year_end, holder, email)
+}
+
+/// Parse the years at the start of the copyright info into the start year and the end year.
+/// The end year can be before the start year if the range is inverted
+fn parse_year_range(years: &str, current_year: u32) -> (u32, u32) {
+    let mut parts = years.splitn(2, '-');
+    let year_start = parts
+        .next()
+        .and_then(|x| cu::parse::<u32>(x).ok())
+        .unwrap_or(DEFAULT_YEAR);
+    let year_end = parts
+        .next()
+        .and_then(|x| parse_year_end(x, current_year))
+        .unwrap_or(year_start);
+    (year_start, year_end)
+}
+
+/// Parse the end year of a range. `present` and `now` mean `current_year`
+fn parse_year_end(year: &str, current_year: u32) -> Option<u32> {
+    if year.eq_ignore_ascii_case("present") || year.eq_ignore_ascii_case("now") {
+        return Some(current_year);
+    }
+    cu::parse::<u32>(year).ok()
+}
+
+/// Split the holder into the name and the email. Only a trailing `<...>`
+/// separated by a space is treated as an email, so angle brackets elsewhere
+/// in the name are kept
+fn split_email(holder: &str) -> (&str, Option<&str>) {
+    let holder = holder.trim();
+    let Some(rest) = holder.strip_suffix('>') else {
+        return (holder, None);
+    };
+    let Some((name, email)) = rest.rsplit_once(" <") else {
+        return (holder, None);
     };
-    let holder = parts.next().unwrap_or("");
-    (year_start, year_end, holder)
+    if email.is_empty() || email.contains(['<', '>']) {
+        return (holder, None);
+    }
+    (name.trim_end(), Some(email))
+}
+
+/// Copyright holders of a file, with a copyright line for each holder in the notice, in order.
+///
+/// Displayed with each holder quoted, like `'A', 'B'`
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Holders(Vec<String>);
+
+impl Holders {
+    /// Create the holders for files that have a copyright line for each holder, in order
+    pub fn new(holders: Vec<String>) -> Self {
+        Self(holders)
+    }
+
+    /// Iterate the holders, in order
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(|x| x.as_str())
+    }
+
+    /// Get the holders as a slice, in order
+    pub fn as_slice(&self) -> &[String] {
+        &self.0
+    }
+
+    /// Get the number of holders
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Check if there are no holders
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Get the holder if there is only one
+    pub fn single(&self) -> Option<&str> {
+        match self.0.as_slice() {
+            [holder] => Some(holder),
+            _ => None,
+        }
+    }
+}
+
+impl From<String> for Holders {
+    fn from(holder: String) -> Self {
+        Self(vec![holder])
+    }
+}
+
+impl From<&str> for Holders {
+    fn from(holder: &str) -> Self {
+        Self(vec![holder.to_string()])
+    }
+}
+
+impl std::fmt::Display for Holders {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, holder) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "'{holder}'")?;
+        }
+        Ok(())
+    }
+}
+
+/// UTF-8 byte order mark
+const BOM: char = '\u{feff}';
+
+/// Get the first line of the content, used for detecting the format
+fn first_line(content: &str) -> &str {
+    content.lines().next().unwrap_or_default()
+}
+
+/// Remove the BOM at the start of the line, if any
+fn strip_bom(line: String) -> String {
+    match line.strip_prefix(BOM) {
+        Some(rest) => rest.to_string(),
+        None => line,
+    }
 }
 
 fn current_year() -> u32 {
@@ -284,6 +2415,20 @@ fn current_year() -> u32 {
     }
     static YEAR: LazyLock<u32> = LazyLock::new(|| {
         use chrono::Datelike;
+        // see https://reproducible-builds.org/specs/source-date-epoch/
+        if let Ok(epoch) = std::env::var("SOURCE_DATE_EPOCH") {
+            match cu::parse::<i64>(&epoch)
+                .ok()
+                .and_then(|x| chrono::DateTime::from_timestamp(x, 0))
+            {
+                Some(time) => {
+                    let y = time.year().max(0) as u32;
+                    cu::debug!("current year is {y} from SOURCE_DATE_EPOCH");
+                    return y;
+                }
+                None => cu::warn!("ignoring invalid SOURCE_DATE_EPOCH '{epoch}'"),
+            }
+        }
         let y = chrono::Local::now().year().max(0) as u32;
         cu::debug!("current year is {y}");
         y
@@ -304,4 +2449,291 @@ mod tests {
             "HASH_FORMAT_EXTENSIONS must be sorted"
         );
     }
+
+    #[test]
+    fn test_paren_format_extensions() {
+        let mut x = PAREN_FORMAT_EXTENSIONS.to_vec();
+        x.sort();
+        assert_eq!(
+            x, PAREN_FORMAT_EXTENSIONS,
+            "PAREN_FORMAT_EXTENSIONS must be sorted"
+        );
+    }
+
+    #[test]
+    fn test_html_format_extensions() {
+        let mut x = HTML_FORMAT_EXTENSIONS.to_vec();
+        x.sort();
+        assert_eq!(
+            x, HTML_FORMAT_EXTENSIONS,
+            "HTML_FORMAT_EXTENSIONS must be sorted"
+        );
+    }
+
+    #[test]
+    fn test_block_format_extensions() {
+        let mut x = BLOCK_FORMAT_EXTENSIONS.to_vec();
+        x.sort();
+        assert_eq!(
+            x, BLOCK_FORMAT_EXTENSIONS,
+            "BLOCK_FORMAT_EXTENSIONS must be sorted"
+        );
+    }
+
+    #[test]
+    fn test_markdown_extensions() {
+        let mut x = MARKDOWN_EXTENSIONS.to_vec();
+        x.sort();
+        assert_eq!(x, MARKDOWN_EXTENSIONS, "MARKDOWN_EXTENSIONS must be sorted");
+    }
+
+    #[test]
+    fn test_slash_format_extensions() {
+        let mut x = SLASH_FORMAT_EXTENSIONS.to_vec();
+        x.sort();
+        assert_eq!(
+            x, SLASH_FORMAT_EXTENSIONS,
+            "SLASH_FORMAT_EXTENSIONS must be sorted"
+        );
+    }
+
+    #[test]
+    fn test_hash_format_file_names() {
+        let mut x = HASH_FORMAT_FILE_NAMES.to_vec();
+        x.sort();
+        assert_eq!(
+            x, HASH_FORMAT_FILE_NAMES,
+            "HASH_FORMAT_FILE_NAMES must be sorted"
+        );
+    }
+
+    #[test]
+    fn test_crlf_dominant() {
+        assert!(!is_crlf_dominant(""));
+        assert!(!is_crlf_dominant("a\nb\r\nc\n"));
+        assert!(is_crlf_dominant("a\r\nb\nc\r\n"));
+        assert!(is_crlf_dominant("a\r\nb\n"));
+        assert!(!is_crlf_dominant("a\nb\r\n"));
+    }
+
+    #[test]
+    fn test_unsupported_extensions() {
+        let mut x = UNSUPPORTED_EXTENSIONS.to_vec();
+        x.sort();
+        assert_eq!(
+            x, UNSUPPORTED_EXTENSIONS,
+            "UNSUPPORTED_EXTENSIONS must be sorted"
+        );
+    }
+
+    #[test]
+    fn test_normalize_typography() {
+        assert_eq!(
+            normalize_typography("\u{201C}Foo\u{201D} Bar\u{2019}s \u{2013} Baz\u{2014}Qux"),
+            "\"Foo\" Bar's - Baz-Qux"
+        );
+        assert_eq!(normalize_typography("Foo Inc."), "Foo Inc.");
+    }
+
+    #[test]
+    fn test_split_email() {
+        assert_eq!(
+            split_email("Jane Doe <jane@example.com>"),
+            ("Jane Doe", Some("jane@example.com"))
+        );
+        assert_eq!(split_email("Jane Doe"), ("Jane Doe", None));
+        assert_eq!(split_email("<Foo> Bar"), ("<Foo> Bar", None));
+        assert_eq!(split_email("Foo <a> <b>"), ("Foo <a>", Some("b")));
+        assert_eq!(split_email("Foo<a>"), ("Foo<a>", None));
+    }
+
+    #[test]
+    fn test_check_strip_spacing() {
+        let f = Format::SlashSlash;
+        assert_eq!(
+            f.check_strip_license_line("//SPDX-License-Identifier:MIT"),
+            Some("MIT")
+        );
+        assert_eq!(
+            f.check_strip_license_line("//  SPDX-License-Identifier :  MIT"),
+            Some("MIT")
+        );
+        assert_eq!(
+            f.check_strip_license_line("// SPDX-License-IdentifierX: MIT"),
+            None
+        );
+        assert_eq!(
+            f.check_strip_license_line("// see SPDX-License-Identifier: MIT"),
+            None
+        );
+        assert_eq!(
+            f.check_strip_license_line("/// SPDX-License-Identifier: MIT"),
+            None
+        );
+        assert!(f.is_canonical_license_line("// SPDX-License-Identifier: MIT"));
+        assert!(!f.is_canonical_license_line("//SPDX-License-Identifier: MIT"));
+        assert_eq!(
+            f.check_strip_copyright_line("//Copyright(c)2020 Foo"),
+            Some("2020 Foo")
+        );
+        assert_eq!(f.check_strip_copyright_line("// Copyright 2020 Foo"), None);
+        assert!(f.is_canonical_copyright_line("// Copyright (c) 2020 Foo", CopyrightStyle::C));
+        assert!(!f.is_canonical_copyright_line("// Copyright (c)  2020 Foo", CopyrightStyle::C));
+    }
+
+    #[test]
+    fn test_fix_keeps_blank_lines_after_notice() -> cu::Result<()> {
+        let options = NoticeOptions::default();
+        let fixed = fixed_content(
+            "// SPDX-License-Identifier: MIT\n// Copyright (c) 2020 Other\n  \n\nfn main() {}\n",
+            Format::SlashSlash,
+            None,
+            &"Holder".into(),
+            "MIT",
+            &options,
+            DEFAULT_YEAR,
+        )?;
+        assert_eq!(
+            fixed,
+            "// SPDX-License-Identifier: MIT\n// Copyright (c) 2020-2025 Holder\n\n\nfn main() {}\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_trims_trailing_whitespace() -> cu::Result<()> {
+        let mut buf = String::new();
+        Format::SlashSlash.format(DEFAULT_YEAR, "Holder ", "MIT ", false, &mut buf)?;
+        assert_eq!(
+            buf,
+            "// SPDX-License-Identifier: MIT\n// Copyright (c) 2025 Holder\n"
+        );
+
+        let options = NoticeOptions::default();
+        let fixed = fixed_content(
+            "  \nfn main() {}\n",
+            Format::SlashSlash,
+            None,
+            &"Holder ".into(),
+            "MIT",
+            &options,
+            DEFAULT_YEAR,
+        )?;
+        assert_eq!(
+            fixed,
+            "// SPDX-License-Identifier: MIT\n// Copyright (c) 2025 Holder\n\nfn main() {}\n"
+        );
+        let lines = fixed.lines().map(|line| Ok(line.to_string()));
+        check_lines(
+            lines,
+            Format::SlashSlash,
+            Path::new("test.rs"),
+            &"Holder ".into(),
+            "MIT",
+            &options,
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_license_matches() {
+        let options = NoticeOptions::default();
+        assert!(options.license_matches("MIT OR Apache-2.0", "Apache-2.0 OR MIT"));
+        assert!(options.license_matches(
+            "(MIT AND BSD-3-Clause) OR Apache-2.0",
+            "Apache-2.0 or (BSD-3-Clause AND MIT)"
+        ));
+        assert!(
+            options.license_matches("(MIT OR ISC) OR Apache-2.0", "MIT OR (Apache-2.0 OR ISC)")
+        );
+        assert!(options.license_matches(
+            "GPL-2.0-only WITH Classpath-exception-2.0 OR MIT",
+            "MIT OR GPL-2.0-only WITH Classpath-exception-2.0"
+        ));
+        assert!(!options.license_matches("MIT", "Apache-2.0"));
+        assert!(!options.license_matches("MIT AND Apache-2.0", "MIT OR Apache-2.0"));
+        assert!(
+            !options.license_matches("(MIT OR ISC) AND Apache-2.0", "MIT OR (ISC AND Apache-2.0)")
+        );
+        assert!(!options.license_matches("MIT OR", "MIT"));
+        assert!(
+            !options
+                .strict()
+                .license_matches("MIT OR Apache-2.0", "Apache-2.0 OR MIT")
+        );
+    }
+
+    #[test]
+    fn test_parse_year_end() {
+        assert_eq!(parse_year_end("2020", DEFAULT_YEAR), Some(2020));
+        assert_eq!(parse_year_end("present", DEFAULT_YEAR), Some(DEFAULT_YEAR));
+        assert_eq!(parse_year_end("Now", 2030), Some(2030));
+        assert_eq!(parse_year_end("later", DEFAULT_YEAR), None);
+        assert_eq!(
+            parse_copyright_info("2020-present Foo", DEFAULT_YEAR),
+            (2020, DEFAULT_YEAR, "Foo", None)
+        );
+    }
+
+    #[test]
+    fn test_detect_from_content() {
+        let detect =
+            |path: &str, first_line: &str| Format::detect_from_content(Path::new(path), first_line);
+        assert_eq!(detect("Dockerfile", "FROM alpine"), Format::Hash);
+        assert_eq!(detect("docker/Makefile", ""), Format::Hash);
+        assert_eq!(detect("CMakeLists.txt", ""), Format::Hash);
+        assert_eq!(detect("run", "#!/bin/sh"), Format::Hash);
+        assert_eq!(detect("run", "#!/usr/bin/env -S python3 -u"), Format::Hash);
+        assert_eq!(detect("run", "#!/usr/bin/env node"), Format::SlashSlash);
+        assert_eq!(detect("run", "echo hello"), Format::SlashSlash);
+        assert_eq!(detect("lib.rs", "#![no_std]"), Format::SlashSlash);
+        // the shebang is used for unknown extensions
+        assert_eq!(detect("run.txt", "#!/bin/sh"), Format::Hash);
+        assert_eq!(detect("run.txt", "echo hello"), Format::SlashSlash);
+        // known extensions take precedence over the shebang
+        assert_eq!(detect("run.js", "#!/bin/sh"), Format::SlashSlash);
+        assert_eq!(detect("run.py", "#!/usr/bin/env node"), Format::Hash);
+    }
+
+    #[test]
+    fn test_min_year_issue() {
+        let options = NoticeOptions {
+            min_year: Some(2000),
+            ..Default::default()
+        };
+        let content = "// SPDX-License-Identifier: MIT\n// Copyright (c) 1990-2025 Foo\n";
+        let e = Format::SlashSlash
+            .check_content(content, &"Foo".into(), "MIT", &options)
+            .unwrap_err();
+        assert_eq!(Issue::from_error(&e).kind, IssueKind::WrongYear);
+    }
+
+    #[test]
+    fn test_header_issue() {
+        let options = NoticeOptions {
+            headers: [("Foo".to_string(), "Copyright {year} {holder}".to_string())].into(),
+            year: Some(DEFAULT_YEAR),
+            ..Default::default()
+        };
+        let content = "// Copyright 2025 Bar\n";
+        let e = Format::SlashSlash
+            .check_content(content, &"Foo".into(), "MIT", &options)
+            .unwrap_err();
+        let issue = Issue::from_error(&e);
+        assert_eq!(issue.kind, IssueKind::Other);
+        assert_eq!(issue.expected.as_deref(), Some("// Copyright 2025 Foo"));
+        assert_eq!(issue.actual.as_deref(), Some("// Copyright 2025 Bar"));
+    }
+
+    #[test]
+    fn test_is_supported() {
+        assert!(is_supported(Path::new("src/main.rs")));
+        assert!(is_supported(Path::new("script.py")));
+        assert!(is_supported(Path::new("Cargo.toml")));
+        assert!(is_supported(Path::new("Makefile")));
+        assert!(!is_supported(Path::new("package.json")));
+        assert!(!is_supported(Path::new("assets/logo.png")));
+        assert!(!is_supported(Path::new("lib.wasm")));
+        assert!(!is_supported(Path::new("assets/logo.PNG")));
+    }
 }
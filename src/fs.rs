@@ -0,0 +1,138 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Pistonite
+
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+
+/// Access to the files being checked and fixed.
+///
+/// This allows running the tool over sources other than the disk, such as
+/// in-memory files or archives. Features that depend on the real disk,
+/// like `only_path` and the git integration, still use the disk.
+pub trait FileSystem: Send + Sync {
+    /// Open the file for reading
+    fn reader(&self, path: &Path) -> cu::Result<Box<dyn BufRead + Send>>;
+    /// Read the whole file as a string
+    fn read_string(&self, path: &Path) -> cu::Result<String>;
+    /// Replace the content of the file
+    fn write(&self, path: &Path, content: &str) -> cu::Result<()>;
+    /// Expand the glob pattern into paths of files. Directories are not included.
+    ///
+    /// The pattern can have `{a,b}` alternations, see [`expand_braces`]
+    fn glob(&self, pattern: &str) -> cu::Result<Vec<PathBuf>>;
+    /// Get the size of the file in bytes
+    fn file_size(&self, path: &Path) -> cu::Result<u64>;
+}
+
+/// The file system on the disk
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DiskFileSystem;
+
+impl FileSystem for DiskFileSystem {
+    fn reader(&self, path: &Path) -> cu::Result<Box<dyn BufRead + Send>> {
+        Ok(Box::new(cu::fs::reader(path)?))
+    }
+
+    fn read_string(&self, path: &Path) -> cu::Result<String> {
+        cu::fs::read_string(path)
+    }
+
+    fn write(&self, path: &Path, content: &str) -> cu::Result<()> {
+        cu::fs::write(path, content)?;
+        Ok(())
+    }
+
+    fn glob(&self, pattern: &str) -> cu::Result<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        for pattern in expand_braces(pattern) {
+            for path in cu::fs::glob(&pattern)? {
+                let path = path?;
+                // the same file can be matched by multiple alternatives
+                if path.is_file() && !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+        }
+        Ok(paths)
+    }
+
+    fn file_size(&self, path: &Path) -> cu::Result<u64> {
+        Ok(path.metadata()?.len())
+    }
+}
+
+/// Expand `{a,b,c}` alternations in the glob pattern into multiple patterns,
+/// since the underlying globber doesn't support them.
+///
+/// Alternations can be nested (`{a,{b,c}}`). Braces without a top-level comma
+/// are kept as is. Use `\{`, `\}` and `\,` to match the characters literally.
+pub fn expand_braces(pattern: &str) -> Vec<String> {
+    let Some((open, close, commas)) = find_brace_group(pattern) else {
+        return vec![unescape_braces(pattern)];
+    };
+    let (prefix, suffix) = (&pattern[..open], &pattern[close + 1..]);
+    let mut bounds = vec![open];
+    bounds.extend(commas);
+    bounds.push(close);
+    bounds
+        .windows(2)
+        .flat_map(|w| {
+            let alternative = &pattern[w[0] + 1..w[1]];
+            expand_braces(&format!("{prefix}{alternative}{suffix}"))
+        })
+        .collect()
+}
+
+/// Find the first brace group with a top-level comma, as the position of
+/// the open brace, the close brace and the top-level commas
+fn find_brace_group(pattern: &str) -> Option<(usize, usize, Vec<usize>)> {
+    let bytes = pattern.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'{' => {
+                if let Some((close, commas)) = match_brace(bytes, i)
+                    && !commas.is_empty()
+                {
+                    return Some((i, close, commas));
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Find the close brace matching the open brace at `open`, and the commas
+/// directly inside the braces
+fn match_brace(bytes: &[u8], open: usize) -> Option<(usize, Vec<usize>)> {
+    let mut depth = 0;
+    let mut commas = Vec::new();
+    let mut i = open;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((i, commas));
+                }
+            }
+            b',' if depth == 1 => commas.push(i),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Replace the escaped braces and commas with patterns that match them literally
+fn unescape_braces(pattern: &str) -> String {
+    pattern
+        .replace("\\{", "[{]")
+        .replace("\\}", "[}]")
+        .replace("\\,", ",")
+}
@@ -0,0 +1,199 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Pistonite
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Run git in the current directory with the args, and get the stdout
+fn git(args: &[&str]) -> cu::Result<String> {
+    cu::debug!("running git {}", args.join(" "));
+    let output = Command::new("git").args(args).output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        cu::bail!("git {} failed: {}", args.join(" "), stderr.trim());
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// Get the root directory of the git repo
+fn repo_root() -> cu::Result<PathBuf> {
+    let root = git(&["rev-parse", "--show-toplevel"])?;
+    Ok(Path::new(root.trim()).canonicalize()?)
+}
+
+/// Get the files and directories ignored by git (by `.gitignore` files, `.git/info/exclude`
+/// and the global excludes file) in the current git repo, as canonicalized paths.
+/// Directories that are entirely ignored are listed instead of the files in them
+pub(crate) fn ignored_paths() -> cu::Result<BTreeSet<PathBuf>> {
+    let root = repo_root()?;
+    let root_str = root.to_string_lossy();
+    // paths are relative to the current directory, so run in the root
+    // to get all ignored paths in the repo
+    let output = git(&[
+        "-C",
+        &root_str,
+        "ls-files",
+        "-z",
+        "--others",
+        "--ignored",
+        "--exclude-standard",
+        "--directory",
+    ])?;
+    let mut paths = BTreeSet::new();
+    for path in output.split('\0') {
+        if path.is_empty() {
+            continue;
+        }
+        if let Ok(path) = root.join(path).canonicalize() {
+            paths.insert(path);
+        }
+    }
+    Ok(paths)
+}
+
+/// Get the year each file was added in the history of the current git repo,
+/// as canonicalized paths. The history is read once for all files.
+///
+/// Renamed files keep the year they were added with the original name.
+/// If not in a git repo, no years are returned, so the current year is used
+pub(crate) fn creation_years() -> cu::Result<BTreeMap<PathBuf, u32>> {
+    let root = match repo_root() {
+        Ok(root) => root,
+        Err(e) => {
+            cu::warn!(
+                "cannot find the git repo, the current year will be used for new notices: {e}"
+            );
+            return Ok(BTreeMap::new());
+        }
+    };
+    let root_str = root.to_string_lossy();
+    let output = git(&[
+        "-C",
+        &root_str,
+        "-c",
+        "core.quotepath=off",
+        "log",
+        "-M",
+        "--diff-filter=AR",
+        "--name-status",
+        "--format=%x00%ad",
+        "--date=format:%Y",
+    ])?;
+    // path in the repo -> year it was added, following renames
+    let mut added = BTreeMap::<&str, u32>::new();
+    // commits are listed from newest to oldest, so renames are followed from the oldest
+    for commit in output.split('\0').rev() {
+        let mut lines = commit.lines();
+        let Some(Ok(year)) = lines.next().map(cu::parse::<u32>) else {
+            continue;
+        };
+        for line in lines {
+            // format is "A\tPATH" or "RXXX\tOLD_PATH\tNEW_PATH"
+            let mut parts = line.split('\t');
+            let status = parts.next().unwrap_or_default();
+            match (parts.next(), parts.next()) {
+                (Some(path), None) if status == "A" => {
+                    added.entry(path).or_insert(year);
+                }
+                (Some(old_path), Some(new_path)) if status.starts_with('R') => {
+                    // the original file may be outside the history read, like in a shallow clone
+                    let year = added.remove(old_path).unwrap_or(year);
+                    added.insert(new_path, year);
+                }
+                _ => {}
+            }
+        }
+    }
+    let mut years = BTreeMap::new();
+    for (path, year) in added {
+        // files deleted later cannot be canonicalized, and don't need a year
+        if let Ok(path) = root.join(path).canonicalize() {
+            years.insert(path, year);
+        }
+    }
+    Ok(years)
+}
+
+/// Get the files with uncommitted changes (including untracked files)
+/// in the current git repo, as canonicalized paths
+pub(crate) fn dirty_files() -> cu::Result<BTreeSet<PathBuf>> {
+    let root = repo_root()?;
+    let status = git(&["status", "--porcelain", "-z", "--untracked-files=all"])?;
+    let mut files = BTreeSet::new();
+    let mut entries = status.split('\0');
+    while let Some(entry) = entries.next() {
+        // format is "XY PATH"
+        let Some((status, path)) = entry.split_at_checked(2) else {
+            continue;
+        };
+        if status.contains(['R', 'C']) {
+            // renames and copies are followed by the original path
+            entries.next();
+        }
+        let path = path.trim_start();
+        if path.is_empty() {
+            continue;
+        }
+        // deleted files cannot be canonicalized, and don't need to be fixed
+        if let Ok(path) = root.join(path).canonicalize() {
+            files.insert(path);
+        }
+    }
+    Ok(files)
+}
+
+/// Get the files with unstaged changes (changes in the working tree that are
+/// not in the index) in the current git repo, as canonicalized paths
+pub(crate) fn unstaged_files() -> cu::Result<BTreeSet<PathBuf>> {
+    let root = repo_root()?;
+    let root_str = root.to_string_lossy();
+    let output = git(&["-C", &root_str, "diff", "--name-only", "-z"])?;
+    let mut files = BTreeSet::new();
+    for path in output.split('\0') {
+        if path.is_empty() {
+            continue;
+        }
+        // deleted files cannot be canonicalized, and don't need to be fixed
+        if let Ok(path) = root.join(path).canonicalize() {
+            files.insert(path);
+        }
+    }
+    Ok(files)
+}
+
+/// Get the files staged in the current git repo that are added, copied or modified,
+/// as absolute paths under the canonicalized repo root
+pub(crate) fn staged_files() -> cu::Result<Vec<PathBuf>> {
+    let root = repo_root()?;
+    let root_str = root.to_string_lossy();
+    let output = git(&[
+        "-C",
+        &root_str,
+        "diff",
+        "--cached",
+        "--name-only",
+        "-z",
+        "--diff-filter=ACM",
+    ])?;
+    Ok(output
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(|path| root.join(path))
+        .collect())
+}
+
+/// Stage the files in the current git repo
+pub(crate) fn stage(paths: &[PathBuf]) -> cu::Result<()> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+    let mut args = vec!["add", "--"];
+    let paths = paths
+        .iter()
+        .map(|x| x.to_string_lossy())
+        .collect::<Vec<_>>();
+    args.extend(paths.iter().map(|x| x.as_ref()));
+    git(&args)?;
+    Ok(())
+}
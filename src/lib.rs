@@ -10,6 +10,19 @@ mod runner;
 pub use runner::*;
 mod format;
 pub use format::*;
+mod suggest;
+pub use suggest::*;
+mod fs;
+mod git;
+pub use fs::*;
+
+#[cfg(feature = "policy")]
+mod policy;
+#[cfg(feature = "policy")]
+pub use policy::*;
+
+#[cfg(feature = "testing")]
+pub mod testing;
 
 #[cfg(feature = "cli")]
 mod cli;
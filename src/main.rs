@@ -1,20 +1,74 @@
 // SPDX-License-Identifier: MIT
 // Copyright (c) 2025-2026 Pistonite
 
-use lisensor::{Cli, config_from_cli, run};
+use lisensor::{
+    Cli, EXIT_ERROR, Failure, auto_extend, auto_extend_path, check_config, config_from_cli,
+    options_from_cli, report_uncovered, run_command, run_stdin, run_with_options,
+};
 
-#[cu::cli(flags = "common")]
+#[cu::cli(flags = "common", preprocess = quiet_for_stdin)]
 async fn main(mut args: Cli) -> cu::Result<()> {
-    let fix = args.fix;
-    let result = run(config_from_cli(&mut args)?, fix).await?;
-
-    if result.is_err() {
-        if fix {
-            cu::bail!("some issues could not be fixed automatically.");
-        } else {
-            cu::bail!("license check unsuccesful.");
+    let fix = args.fix && !args.dry_run;
+    // issues and errors are distinguished by the exit code.
+    // Returning an error exits with 1, which is EXIT_ISSUES
+    match main_internal(&mut args).await {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(_)) => {
+            if fix {
+                cu::bail!("some issues could not be fixed automatically.");
+            } else {
+                cu::bail!("license check unsuccesful.");
+            }
+        }
+        Err(e) => {
+            cu::error!("fatal: {e:?}");
+            std::process::exit(EXIT_ERROR.into());
         }
     }
+}
+
+/// Logs are printed to stdout, which only has the content in stdin mode
+fn quiet_for_stdin(args: &mut Cli) {
+    if args.stdin {
+        args.common.verbose = 0;
+        args.common.quiet = 2;
+    }
+}
 
-    Ok(())
+async fn main_internal(args: &mut Cli) -> cu::Result<Result<(), Failure>> {
+    if let Some(command) = args.command.take() {
+        run_command(command)?;
+        return Ok(Ok(()));
+    }
+    if args.stdin {
+        // globs and config files are not used in stdin mode
+        return run_stdin(args, &mut std::io::stdin(), &mut std::io::stdout());
+    }
+    let options = options_from_cli(args);
+    let auto_extend_path = if args.auto_extend {
+        Some(auto_extend_path(args)?)
+    } else {
+        None
+    };
+    #[cfg(feature = "policy")]
+    let mut config = match args.policy.take() {
+        Some(url) => lisensor::config_from_policy(&url, args).await?,
+        None => config_from_cli(args)?,
+    };
+    #[cfg(not(feature = "policy"))]
+    let mut config = config_from_cli(args)?;
+    if let Some(path) = auto_extend_path {
+        auto_extend(&path, &mut config, args.yes)?;
+    }
+    #[cfg(feature = "spdx")]
+    config.validate()?;
+    if args.check_config {
+        // conflicts are already detected when building the config
+        check_config(&config, &mut std::io::stdout())?;
+        return Ok(Ok(()));
+    }
+    if let Some(root) = args.report_uncovered.take() {
+        return report_uncovered(&root, &config, &args.extensions);
+    }
+    run_with_options(config, options).await
 }
@@ -0,0 +1,50 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Pistonite
+
+use std::path::Path;
+
+use crate::Config;
+
+/// Default file for caching the policy fetched from a URL
+pub const POLICY_CACHE_FILE: &str = ".lisensor-policy.toml";
+
+/// Fetch a shared policy config from `url`.
+///
+/// The policy has the same format as a config file, and the globs in it are
+/// resolved relative to `parent`. A successfully fetched policy is cached at
+/// `cache_path`. If the policy cannot be fetched (for example, when offline), the
+/// cached policy is used instead. `None` is returned if neither is available.
+pub async fn fetch_policy(url: &str, parent: &Path, cache_path: &Path) -> Option<Config> {
+    match fetch_policy_content(url).await {
+        Ok(content) => match Config::parse(&content, parent, url) {
+            Ok(config) => {
+                if let Err(e) = cu::fs::write(cache_path, content) {
+                    cu::warn!("failed to cache policy at '{}': {e}", cache_path.display());
+                }
+                return Some(config);
+            }
+            Err(e) => cu::warn!("failed to parse policy from '{url}': {e}"),
+        },
+        Err(e) => cu::warn!("failed to fetch policy from '{url}': {e}"),
+    }
+
+    if !cache_path.exists() {
+        return None;
+    }
+    cu::info!("using cached policy from '{}'", cache_path.display());
+    let result = cu::fs::read_string(cache_path)
+        .and_then(|content| Config::parse(&content, parent, &cache_path.display().to_string()));
+    match result {
+        Ok(config) => Some(config),
+        Err(e) => {
+            cu::warn!("failed to load cached policy: {e}");
+            None
+        }
+    }
+}
+
+async fn fetch_policy_content(url: &str) -> cu::Result<String> {
+    cu::debug!("fetching policy from '{url}'");
+    let response = reqwest::get(url).await?.error_for_status()?;
+    Ok(response.text().await?)
+}
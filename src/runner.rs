@@ -1,34 +1,259 @@
 // SPDX-License-Identifier: MIT
 // Copyright (c) 2025-2026 Pistonite
 
-use std::collections::BTreeMap;
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
-use crate::{Config, format};
+use cu::pre::*;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::{
+    Config, DiskFileSystem, FileSystem, Format, Holders, Issue, IssueKind, NoticeOptions, format,
+};
+
+/// Options for running the tool
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RunOptions {
+    /// Attempt to fix the issues found
+    pub fix: bool,
+    /// In fix mode, only fix the files matching this glob. Other files
+    /// are still checked, but not modified.
+    pub only_path: Option<String>,
+    /// If set, limit the total size (in bytes) of the files being processed at
+    /// the same time, so many large files are not loaded into memory at once.
+    pub memory_budget: Option<u64>,
+    /// In fix mode, do not modify files with uncommitted changes in git.
+    /// Such files that need fixing are reported as errors instead.
+    pub no_fix_if_uncommitted: bool,
+    /// In fix mode, abort without modifying any file if more than this
+    /// number of files would be changed
+    pub max_changes: Option<usize>,
+    /// In fix mode, only fix files where the copyright year is the only issue.
+    /// Other files are reported as errors
+    pub year_only: bool,
+    /// Skip files ignored by git in the current repo
+    pub respect_gitignore: bool,
+    /// Format of the output at the end of the run
+    pub output_format: OutputFormat,
+    /// Remove the license notices instead of checking or fixing them
+    pub remove: bool,
+    /// Only list the files matched by each glob, without reading or modifying them
+    pub list: bool,
+    /// In check mode, print a diff of what fixing would change for each
+    /// file with issues, without modifying any file
+    pub diff: bool,
+    /// In fix mode, use the year the file was added in git as the start year
+    /// for new notices. Files not tracked by git (or outside a git repo) use the current year
+    pub git_year: bool,
+    /// Maximum number of files processed at the same time. If not set,
+    /// [`DEFAULT_JOBS`] is used
+    pub jobs: Option<usize>,
+    /// Only check (or fix) the files staged in git, instead of expanding the globs.
+    /// Staged files not matched by any glob are ignored. In fix mode, the fixed files
+    /// are staged again, and files with unstaged changes are not fixed
+    pub staged: bool,
+    /// Skip files larger than this size (in bytes). Files that look binary
+    /// (with a NUL byte near the start) are always skipped
+    pub max_file_size: Option<u64>,
+    /// Only print the final counts, instead of logging each file with issues.
+    /// The first N files with issues (can be 0) are listed after the counts.
+    /// The returned [`Failure`] still has all the files
+    pub summary: Option<usize>,
+    /// Stop at the first file with issues, and abort processing the other files.
+    /// Files already processed are still fixed in fix mode
+    pub fail_fast: bool,
+    /// In fix mode, compute the fixes without writing any file. Files that
+    /// would be fixed are reported in the [`Failure`], with [`FileFailure::fixable`] set
+    pub dry_run: bool,
+}
+
+/// Default for [`RunOptions::jobs`]
+pub const DEFAULT_JOBS: usize = 1024;
+
+/// Format of the output at the end of the run
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Log the issues and a summary for humans
+    #[default]
+    Text,
+    /// Print a JSON array with the path, format, status, expected and actual
+    /// values, and message for each file
+    Json,
+}
 
 /// Issues found
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct Failure {
-    pub errors: Vec<String>,
+    /// The files with issues, sorted by path
+    pub failures: Vec<FileFailure>,
 }
 
 impl std::fmt::Display for Failure {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for e in &self.errors {
-            e.fmt(f)?;
+        for failure in &self.failures {
+            failure.message.fmt(f)?;
             writeln!(f)?;
         }
         Ok(())
     }
 }
 
+/// A file with issues that are not fixed
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileFailure {
+    pub path: PathBuf,
+    pub kind: IssueKind,
+    pub message: String,
+    /// If the issues would be fixed, in a dry run of fix mode
+    pub fixable: bool,
+}
+
+impl Failure {
+    fn from_results(results: Vec<FileResult>) -> Self {
+        let failures = results
+            .into_iter()
+            .filter_map(|result| {
+                let issue = result.issue?;
+                Some(FileFailure {
+                    path: result.path,
+                    kind: issue.kind,
+                    message: issue.message().to_string(),
+                    fixable: result.fixable,
+                })
+            })
+            .collect();
+        Self { failures }
+    }
+}
+
+/// Result of checking or fixing one file
+struct FileResult {
+    path: PathBuf,
+    /// The format of the notice for the file
+    format: Format,
+    /// The issue found, `None` if the file is ok
+    issue: Option<Issue>,
+    /// If the issue would be fixed, in a dry run
+    fixable: bool,
+}
+
+impl FileResult {
+    /// Get the status of the file used in the JSON output, which is `ok`
+    /// or the name of the issue kind
+    fn status(&self) -> &'static str {
+        match &self.issue {
+            None => "ok",
+            Some(issue) => issue.kind.name(),
+        }
+    }
+}
+
 /// Run the tool for the given config.
 ///
 /// - `Ok(Ok(())` means successful.
 /// - `Ok(Err(failure))` means the run was successful, but issues are found.
 /// - `Err(e)` means the run itself was not successful.
 pub async fn run(config: Config, fix: bool) -> cu::Result<Result<(), Failure>> {
+    run_with_options(
+        config,
+        RunOptions {
+            fix,
+            ..Default::default()
+        },
+    )
+    .await
+}
+
+/// Run the tool for the given config with additional options.
+///
+/// See [`run`] for the meaning of the return value.
+pub async fn run_with_options(
+    config: Config,
+    options: RunOptions,
+) -> cu::Result<Result<(), Failure>> {
+    run_with_output(config, options, None).await
+}
+
+/// Run the tool for the given config with additional options, writing
+/// the summary at the end to `out` instead of logging it.
+///
+/// This is useful for embedding the tool in another program.
+/// See [`run`] for the meaning of the return value.
+pub async fn run_with_output(
+    config: Config,
+    options: RunOptions,
+    out: Option<&mut (dyn Write + Send)>,
+) -> cu::Result<Result<(), Failure>> {
+    run_with_fs(config, options, Arc::new(DiskFileSystem), out).await
+}
+
+/// Run the tool for the given config with additional options, calling `callback`
+/// with an event for each file as it's processed, alongside the built-in progress bar.
+///
+/// This is useful for embedding the tool in another program with its own UI.
+/// See [`run`] for the meaning of the return value.
+pub async fn run_with(
+    config: Config,
+    options: RunOptions,
+    mut callback: impl FnMut(RunEvent<'_>) + Send,
+) -> cu::Result<Result<(), Failure>> {
+    run_impl(
+        config,
+        options,
+        Arc::new(DiskFileSystem),
+        None,
+        &mut callback,
+    )
+    .await
+}
+
+/// Event for a file, passed to the callback in [`run_with`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RunEvent<'a> {
+    /// The file is checked (or processed in fix or remove mode), with the issue
+    /// found if any
+    FileChecked {
+        path: &'a Path,
+        result: Result<(), &'a Issue>,
+    },
+    /// The file is fixed (or its notice is removed). If the writes are deferred,
+    /// such as with [`RunOptions::max_changes`], it's written after all files are processed
+    FileFixed { path: &'a Path },
+    /// The file is skipped because it's binary or too large
+    FileSkipped { path: &'a Path },
+}
+
+/// Run the tool for the given config, accessing the files through `fs`
+/// instead of the disk.
+///
+/// See [`run_with_output`] for the meaning of `out` and [`run`] for
+/// the meaning of the return value.
+pub async fn run_with_fs(
+    config: Config,
+    options: RunOptions,
+    fs: Arc<dyn FileSystem>,
+    out: Option<&mut (dyn Write + Send)>,
+) -> cu::Result<Result<(), Failure>> {
+    run_impl(config, options, fs, out, &mut |_| {}).await
+}
+
+async fn run_impl(
+    config: Config,
+    options: RunOptions,
+    fs: Arc<dyn FileSystem>,
+    mut out: Option<&mut (dyn Write + Send)>,
+    callback: &mut (dyn FnMut(RunEvent<'_>) + Send),
+) -> cu::Result<Result<(), Failure>> {
+    // listing never modifies files
+    let fix = options.fix && !options.list;
+    let json = options.output_format == OutputFormat::Json;
+    let jobs = options.jobs.unwrap_or(DEFAULT_JOBS);
+    if jobs == 0 {
+        cu::bail!("the number of jobs must be at least 1");
+    }
+    let jobs = cu::check!(isize::try_from(jobs), "too many jobs: {jobs}")?;
     let bar = cu::progress(if fix {
         "fixing files"
     } else {
@@ -38,23 +263,87 @@ pub async fn run(config: Config, fix: bool) -> cu::Result<Result<(), Failure>> {
     .total(0)
     .spawn();
 
-    let mut path_map = BTreeMap::new();
-    let mut handles = Vec::new();
+    let fix_only = match &options.only_path {
+        Some(glob) if fix => Some(glob_path_set(glob)?),
+        _ => None,
+    };
+
+    let dirty = if fix && options.no_fix_if_uncommitted {
+        Some(crate::git::dirty_files()?)
+    } else if fix && options.staged {
+        // staging the fixed file again would also stage the unstaged changes
+        Some(crate::git::unstaged_files()?)
+    } else {
+        None
+    };
+
+    let git_years = if fix && options.git_year {
+        Some(crate::git::creation_years()?)
+    } else {
+        None
+    };
+
+    let ignored = if options.respect_gitignore {
+        Some(crate::git::ignored_paths()?)
+    } else {
+        None
+    };
+
+    let staged = if options.staged {
+        Some(StagedFiles::new(crate::git::staged_files()?)?)
+    } else {
+        None
+    };
+    // staged files are often matched by only a few globs
+    let warn_no_match = staged.is_none();
+
+    let mut excluded = BTreeSet::new();
+    for pattern in config.excludes() {
+        // `dir/**` only matches the directories, so match the files in them instead
+        let pattern = if pattern.ends_with("/**") {
+            format!("{pattern}/*")
+        } else {
+            pattern.clone()
+        };
+        for path in fs.glob(&pattern)? {
+            excluded.insert(normalize_path(path));
+        }
+    }
+
+    let mut state = RunState {
+        fix,
+        fix_only,
+        notice_options: Arc::new(config.notice_options().clone()),
+        budget: options.memory_budget.map(ByteBudget::new),
+        dirty,
+        excluded,
+        ignored,
+        git_years,
+        // removing always defers, to count the changed files
+        defer_write: options.remove || (fix && options.max_changes.is_some()),
+        dry_run: fix && options.dry_run,
+        remove: options.remove,
+        diffs: (options.diff && !fix && !json).then(Default::default),
+        listed: options.list.then(Vec::new),
+        year_only: options.year_only,
+        log_issues: !json && options.summary.is_none(),
+        quiet: options.summary.is_some(),
+        max_file_size: options.max_file_size,
+        staged,
+        fs: Arc::clone(&fs),
+        // avoid opening too many files
+        pool: cu::co::pool(jobs),
+        handles: Vec::new(),
+        path_map: BTreeMap::new(),
+    };
     let mut no_match_glob = Vec::new();
     let mut glob_errors = Vec::new();
 
-    // avoid opening too many files. max open 1024 files
-    let pool = cu::co::pool(1024);
+    // paths from Config::from_paths are not expanded as globs
+    let literal_paths = config.literal_paths().clone();
     for (glob, holder, license) in config.into_iter() {
-        let result = run_glob(
-            &glob,
-            holder,
-            license,
-            fix,
-            &pool,
-            &mut handles,
-            &mut path_map,
-        );
+        let literal = literal_paths.contains(&glob);
+        let result = run_glob(&glob, literal, holder, license, &mut state);
         match result {
             Ok(matched) => {
                 if !matched {
@@ -66,11 +355,17 @@ pub async fn run(config: Config, fix: bool) -> cu::Result<Result<(), Failure>> {
             }
         }
     }
+    if warn_no_match {
+        for glob in &no_match_glob {
+            cu::warn!("glob '{glob}' did not match any file");
+        }
+    }
     // put handles into a set to be auto aborted
     // with error handling below
-    let total = handles.len();
+    let total = state.handles.len();
     bar.set_total(total as u64);
-    let mut set = cu::co::set(handles);
+    let mut set = cu::co::set(state.handles);
+    let diffs = state.diffs.take();
 
     // handle glob errors first
     if !glob_errors.is_empty() {
@@ -84,103 +379,875 @@ pub async fn run(config: Config, fix: bool) -> cu::Result<Result<(), Failure>> {
         cu::bail!("error while searching for files");
     }
 
+    if let Some(listed) = state.listed.take() {
+        let output = listed_to_string(listed);
+        match out {
+            Some(out) => write!(out, "{output}")?,
+            None => print!("{output}"),
+        }
+        return Ok(Ok(()));
+    }
+
     let mut errors = vec![];
+    let mut results = vec![];
+    let mut pending_writes = vec![];
+    let mut holder_summaries = BTreeMap::<Arc<Holders>, HolderSummary>::new();
+    let mut skipped_count = 0;
+    let mut fixed_paths = vec![];
+    let mut stopped = false;
     while let Some(result) = set.next().await {
         // join error
-        let (path, result) = result?;
+        let TaskOutput {
+            path,
+            holder,
+            fixed,
+            skipped,
+            result,
+        } = result?;
+        cu::progress!(bar += 1, "{}", path.display());
+        if skipped {
+            callback(RunEvent::FileSkipped { path: &path });
+            skipped_count += 1;
+            continue;
+        }
+        // the format is only used in the JSON output, and may depend on the shebang
+        let format = if json {
+            format::format_for_file(fs.as_ref(), &path, &state.notice_options)
+        } else {
+            state.notice_options.format_for(&path)
+        };
+        let summary = holder_summaries.entry(holder).or_default();
+        if result.is_err() {
+            summary.failed += 1;
+        } else if fixed {
+            summary.fixed += 1;
+            fixed_paths.push(path.clone());
+        } else {
+            summary.ok += 1;
+        }
         // handle check error
-        if let Err(e) = result {
-            errors.push(e)
+        let issue = match result {
+            Err(e) => {
+                let issue = Issue::from_error(&e);
+                errors.push(e);
+                Some(issue)
+            }
+            Ok(Some(content)) => {
+                pending_writes.push((path.clone(), content));
+                None
+            }
+            Ok(None) => None,
+        };
+        callback(RunEvent::FileChecked {
+            path: &path,
+            result: issue.as_ref().map_or(Ok(()), Err),
+        });
+        if fixed && issue.is_none() {
+            callback(RunEvent::FileFixed { path: &path });
+        }
+        let has_issue = issue.is_some();
+        results.push(FileResult {
+            path,
+            format,
+            issue,
+            fixable: fixed && has_issue,
+        });
+        if options.fail_fast && has_issue {
+            stopped = true;
+            break;
+        }
+    }
+    // abort the remaining tasks
+    drop(set);
+    results.sort_by(|a, b| a.path.cmp(&b.path));
+    let total = if stopped {
+        cu::debug!("stopping at the first issue");
+        results.len()
+    } else {
+        // skipped files are not counted
+        total - skipped_count
+    };
+
+    if let Some(max_changes) = options.max_changes
+        && pending_writes.len() > max_changes
+    {
+        cu::bail!(
+            "fixing would change {} files, which is more than --max-changes {max_changes}. No files are modified.",
+            pending_writes.len()
+        );
+    }
+    let changed = pending_writes.len();
+    if !state.dry_run {
+        for (path, content) in pending_writes {
+            fs.write(&path, &content)?;
+        }
+    }
+    if options.staged {
+        // so the fixes are included in the commit
+        crate::git::stage(&fixed_paths)?;
+    }
+
+    if json {
+        let output = results_to_json(&results)?;
+        match out {
+            Some(out) => writeln!(out, "{output}")?,
+            None => println!("{output}"),
+        }
+        if errors.is_empty() {
+            return Ok(Ok(()));
+        }
+        return Ok(Err(Failure::from_results(results)));
+    }
+    // removing is summarized separately below
+    let holder_summary = if options.remove {
+        None
+    } else {
+        holder_summary_line(&holder_summaries)
+    };
+
+    if let Some(diffs) = diffs {
+        let diffs = std::mem::take(&mut *diffs.lock().unwrap_or_else(|e| e.into_inner()));
+        for diff in diffs.values() {
+            match &mut out {
+                Some(out) => write!(out, "{diff}")?,
+                None => print!("{diff}"),
+            }
         }
-        cu::progress!(bar += 1, "{}", path.display());
     }
 
     if !errors.is_empty() {
         let failed = errors.len();
-        cu::error!("checked {total} files, found {failed} issue(s).");
-        cu::hint!("run with --fix to fix them automatically.");
-
-        let errors = errors.into_iter().map(|x| x.to_string()).collect();
+        let stale = errors
+            .iter()
+            .filter(|e| e.downcast_ref::<format::StaleYear>().is_some())
+            .count();
+        let would_fix = results.iter().filter(|x| x.fixable).count();
+        let hint = if state.dry_run {
+            format!("{would_fix} of them would be fixed, run without --dry-run to fix them.")
+        } else if stale == failed {
+            "only copyright years are outdated, run with --fix --year-only to update them automatically."
+                .to_string()
+        } else if stale > 0 {
+            format!(
+                "run with --fix to fix them automatically. {stale} of them only have outdated copyright years, which --fix --year-only can fix safely."
+            )
+        } else {
+            "run with --fix to fix them automatically.".to_string()
+        };
+        let failed_paths = options.summary.map(|max| {
+            results
+                .iter()
+                .filter(|result| result.issue.is_some())
+                .take(max)
+                .map(|result| result.path.display().to_string())
+                .collect::<Vec<_>>()
+        });
+        match out {
+            Some(out) => {
+                for path in failed_paths.iter().flatten() {
+                    writeln!(out, "{path}")?;
+                }
+                writeln!(out, "checked {total} files, found {failed} issue(s).")?;
+                if let Some(holder_summary) = &holder_summary {
+                    writeln!(out, "{holder_summary}")?;
+                }
+                writeln!(out, "{hint}")?;
+            }
+            None => {
+                for path in failed_paths.iter().flatten() {
+                    cu::warn!("'{path}' has issues");
+                }
+                cu::error!("checked {total} files, found {failed} issue(s).");
+                if let Some(holder_summary) = &holder_summary {
+                    cu::info!("{holder_summary}");
+                }
+                cu::hint!("{hint}");
+            }
+        }
 
-        return Ok(Err(Failure { errors }));
+        return Ok(Err(Failure::from_results(results)));
     }
 
-    cu::info!("license check successful for {total} files.");
+    let summary = if options.remove {
+        format!("removed license notices from {changed} of {total} files.")
+    } else {
+        format!("license check successful for {total} files.")
+    };
+    match out {
+        Some(out) => {
+            writeln!(out, "{summary}")?;
+            if let Some(holder_summary) = &holder_summary {
+                writeln!(out, "{holder_summary}")?;
+            }
+        }
+        None => {
+            cu::info!("{summary}");
+            if let Some(holder_summary) = &holder_summary {
+                cu::info!("{holder_summary}");
+            }
+        }
+    }
     Ok(Ok(()))
 }
 
+/// Render the results as a JSON array
+fn results_to_json(results: &[FileResult]) -> cu::Result<String> {
+    let results = results.iter().map(JsonResult::new).collect::<Vec<_>>();
+    Ok(serde_json::to_string_pretty(&results)?)
+}
+
+/// Result of a file in the JSON output
+#[derive(Serialize)]
+struct JsonResult<'a> {
+    path: String,
+    format: &'static str,
+    status: &'static str,
+    expected: Option<&'a str>,
+    actual: Option<&'a str>,
+    message: Option<&'a str>,
+}
+
+impl<'a> JsonResult<'a> {
+    fn new(result: &'a FileResult) -> Self {
+        let issue = result.issue.as_ref();
+        Self {
+            path: result.path.to_string_lossy().into_owned(),
+            format: result.format.name(),
+            status: result.status(),
+            expected: issue.and_then(|x| x.expected.as_deref()),
+            actual: issue.and_then(|x| x.actual.as_deref()),
+            message: issue.map(|x| x.message()),
+        }
+    }
+}
+
+/// Files matched by a glob in list mode, as (glob, holder, license, files)
+type ListedGlob = (String, Arc<Holders>, Arc<String>, Vec<PathBuf>);
+
+/// Render the files matched by each glob, with the holder and license for them
+fn listed_to_string(listed: Vec<ListedGlob>) -> String {
+    use std::fmt::Write as _;
+    let mut out = String::new();
+    for (glob, holder, license, mut files) in listed {
+        let _ = writeln!(out, "{glob} (holder {holder}, license '{license}'):");
+        if files.is_empty() {
+            out.push_str("  (no files)\n");
+        }
+        files.sort();
+        for file in files {
+            let _ = writeln!(out, "  {}", file.display());
+        }
+    }
+    out
+}
+
+/// Output of the task for a file
+struct TaskOutput {
+    path: PathBuf,
+    holder: Arc<Holders>,
+    /// If the file is fixed, or the fixed content is pending to be written.
+    /// In a dry run, the result is the issue that would be fixed
+    fixed: bool,
+    /// If the file is skipped because it's binary or too large
+    skipped: bool,
+    /// `Ok(Some(content))` means the fixed content is pending to be written
+    result: cu::Result<Option<String>>,
+}
+
+impl TaskOutput {
+    fn new(path: PathBuf, holder: Arc<Holders>, result: cu::Result<Option<String>>) -> Self {
+        Self {
+            path,
+            holder,
+            fixed: false,
+            skipped: false,
+            result,
+        }
+    }
+
+    fn skipped(path: PathBuf, holder: Arc<Holders>) -> Self {
+        Self {
+            skipped: true,
+            ..Self::new(path, holder, Ok(None))
+        }
+    }
+}
+
+/// Files staged in git, used instead of expanding the globs
+struct StagedFiles {
+    /// The current directory, canonicalized
+    cwd: PathBuf,
+    /// Absolute paths of the staged files
+    paths: Vec<PathBuf>,
+}
+
+impl StagedFiles {
+    fn new(paths: Vec<PathBuf>) -> cu::Result<Self> {
+        let cwd = std::env::current_dir()?.canonicalize()?;
+        Ok(Self { cwd, paths })
+    }
+
+    /// Check if the file at `path` is staged
+    fn contains(&self, path: &Path) -> cu::Result<bool> {
+        Ok(self.paths.contains(&path.canonicalize()?))
+    }
+
+    /// Get the staged files matching the glob, relative to the current directory if possible
+    fn matching(&self, glob: &str) -> cu::Result<Vec<PathBuf>> {
+        let mut patterns = Vec::new();
+        for glob in crate::expand_braces(glob) {
+            let pattern = self.absolute_pattern(&glob);
+            patterns.push(cu::check!(
+                glob::Pattern::new(&pattern),
+                "invalid glob pattern '{glob}'"
+            )?);
+        }
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..Default::default()
+        };
+        Ok(self
+            .paths
+            .iter()
+            .filter(|path| patterns.iter().any(|x| x.matches_path_with(path, options)))
+            .map(|path| path.strip_prefix(&self.cwd).unwrap_or(path).to_path_buf())
+            .collect())
+    }
+
+    /// Make the glob absolute, with the part before any wildcard canonicalized,
+    /// so it can be matched against the staged paths
+    fn absolute_pattern(&self, glob: &str) -> String {
+        let mut prefix = PathBuf::new();
+        let mut rest = PathBuf::new();
+        for component in self.cwd.join(glob).components() {
+            use std::path::Component;
+            let is_literal = rest.as_os_str().is_empty()
+                && !component
+                    .as_os_str()
+                    .to_string_lossy()
+                    .contains(['*', '?', '[']);
+            let part = if is_literal { &mut prefix } else { &mut rest };
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    part.pop();
+                }
+                component => part.push(component),
+            }
+        }
+        let prefix = prefix.canonicalize().unwrap_or(prefix);
+        prefix.join(rest).to_string_lossy().into_owned()
+    }
+}
+
+/// Check if the file should be skipped because it's binary or larger than `max_file_size`.
+///
+/// Errors are ignored here, so they are reported when the file is processed
+fn is_skipped(fs: &dyn FileSystem, path: &Path, max_file_size: Option<u64>) -> bool {
+    if let Some(max) = max_file_size
+        && fs.file_size(path).is_ok_and(|size| size > max)
+    {
+        cu::debug!("skipping file larger than {max} bytes '{}'", path.display());
+        return true;
+    }
+    let Ok(mut reader) = fs.reader(path) else {
+        return false;
+    };
+    // only the first chunk is looked at
+    let is_binary = reader.fill_buf().is_ok_and(|chunk| chunk.contains(&0));
+    if is_binary {
+        cu::debug!("skipping binary file '{}'", path.display());
+    }
+    is_binary
+}
+
+/// Number of files in each state for a holder
+#[derive(Debug, Default, Clone, Copy)]
+struct HolderSummary {
+    ok: usize,
+    fixed: usize,
+    failed: usize,
+}
+
+/// Render the summary for each holder in one line, or `None` if there's only one holder
+fn holder_summary_line(summaries: &BTreeMap<Arc<Holders>, HolderSummary>) -> Option<String> {
+    if summaries.len() <= 1 {
+        return None;
+    }
+    let parts = summaries
+        .iter()
+        .map(|(holder, summary)| {
+            let mut part = format!("{holder}: {} files ok", summary.ok);
+            if summary.fixed > 0 {
+                part.push_str(&format!(", {} fixed", summary.fixed));
+            }
+            if summary.failed > 0 {
+                part.push_str(&format!(", {} with issues", summary.failed));
+            }
+            part
+        })
+        .collect::<Vec<_>>();
+    Some(parts.join("; "))
+}
+
+/// State shared by all globs in a run
+struct RunState {
+    fix: bool,
+    /// If set, only files in this set (canonicalized) are fixed
+    fix_only: Option<BTreeSet<PathBuf>>,
+    notice_options: Arc<NoticeOptions>,
+    budget: Option<ByteBudget>,
+    /// If set, files in this set (canonicalized) have uncommitted changes
+    /// and should not be fixed
+    dirty: Option<BTreeSet<PathBuf>>,
+    /// Files matched by the exclude patterns (see [`normalize_path`]), which are skipped
+    excluded: BTreeSet<PathBuf>,
+    /// If set, files in this set (canonicalized) or under directories
+    /// in this set are ignored by git and skipped
+    ignored: Option<BTreeSet<PathBuf>>,
+    /// If set, the year each file (canonicalized) was added in git,
+    /// used as the start year for new notices
+    git_years: Option<BTreeMap<PathBuf, u32>>,
+    /// If fixed content should be returned from the tasks, to be
+    /// written after all files are processed
+    defer_write: bool,
+    /// If the fixes are only computed, without writing the files
+    dry_run: bool,
+    /// If only files with outdated copyright years should be fixed
+    year_only: bool,
+    /// If the notices should be removed instead of checked
+    remove: bool,
+    /// If set, the files matched by each glob are collected here as (glob, holder,
+    /// license, files), instead of being processed
+    listed: Option<Vec<ListedGlob>>,
+    /// If set, diffs of what fixing would change for the files with issues are
+    /// collected here in check mode
+    diffs: Option<Arc<Mutex<BTreeMap<PathBuf, String>>>>,
+    /// If issues are logged for each file as they are found
+    log_issues: bool,
+    /// If errors for each file are not logged, only the final counts
+    quiet: bool,
+    /// Files larger than this are skipped
+    max_file_size: Option<u64>,
+    /// If set, only these files are processed, instead of expanding the globs
+    staged: Option<StagedFiles>,
+    fs: Arc<dyn FileSystem>,
+    pool: cu::co::Pool,
+    handles: Vec<cu::co::Handle<TaskOutput>>,
+    path_map: BTreeMap<PathBuf, (Arc<Holders>, Arc<String>)>,
+}
+
+impl RunState {
+    /// Check if the file at `path` should be fixed, or only checked
+    fn should_fix(&self, path: &Path) -> cu::Result<bool> {
+        if !self.fix {
+            return Ok(false);
+        }
+        match &self.fix_only {
+            None => Ok(true),
+            Some(set) => Ok(set.contains(&path.canonicalize()?)),
+        }
+    }
+
+    /// Record that the file at `path` is fixed with the holder and license from `glob`.
+    /// Returns `false` if the file is already fixed by a previous glob with the same
+    /// holder and license, and errors if the holder or license conflicts
+    fn record_fix(
+        &mut self,
+        path: &Path,
+        holder: &Arc<Holders>,
+        license: &Arc<String>,
+        glob: &str,
+    ) -> cu::Result<bool> {
+        use std::collections::btree_map::Entry;
+        match self.path_map.entry(path.to_path_buf()) {
+            Entry::Occupied(e) => {
+                let (existing_h, existing_l) = e.get();
+                if (existing_h, existing_l) != (holder, license) {
+                    cu::error!(
+                        "file '{}' matched by multiple globs of conflicting config!",
+                        e.key().display()
+                    );
+                    cu::error!("- in one config, it has holder {holder} and license '{license}'");
+                    cu::error!(
+                        "- in another, it has holder {existing_h} and license '{existing_l}'"
+                    );
+                    cu::bail!(
+                        "conflicting config found for '{}', while globbing '{glob}'",
+                        e.key().display()
+                    );
+                }
+                Ok(false)
+            }
+            Entry::Vacant(e) => {
+                e.insert((Arc::clone(holder), Arc::clone(license)));
+                Ok(true)
+            }
+        }
+    }
+
+    /// Check if the file at `path` is ignored by git
+    fn is_ignored(&self, path: &Path) -> cu::Result<bool> {
+        match &self.ignored {
+            None => Ok(false),
+            Some(set) => Ok(path.canonicalize()?.ancestors().any(|x| set.contains(x))),
+        }
+    }
+
+    /// Get the start year for a new notice in the file at `path`, if it
+    /// should not be the current year
+    fn new_notice_year(&self, path: &Path) -> cu::Result<Option<u32>> {
+        match &self.git_years {
+            None => Ok(None),
+            Some(years) => Ok(years.get(&path.canonicalize()?).copied()),
+        }
+    }
+
+    /// Check if the file at `path` has uncommitted changes that
+    /// prevent it from being fixed
+    fn is_dirty(&self, path: &Path) -> cu::Result<bool> {
+        match &self.dirty {
+            None => Ok(false),
+            Some(set) => Ok(set.contains(&path.canonicalize()?)),
+        }
+    }
+}
+
+/// Canonicalize the path if possible, for comparing paths from different globs.
+/// Paths not on the disk are kept as is
+fn normalize_path(path: PathBuf) -> PathBuf {
+    path.canonicalize().unwrap_or(path)
+}
+
+/// Limit for the total size of files being processed at the same time
+#[derive(Clone)]
+struct ByteBudget {
+    semaphore: Arc<Semaphore>,
+    /// Total number of permits, each permit is 1 KiB
+    total_kib: u32,
+}
+
+impl ByteBudget {
+    fn new(bytes: u64) -> Self {
+        let total_kib = bytes.div_ceil(1024).clamp(1, u32::MAX as u64) as u32;
+        Self {
+            semaphore: Arc::new(Semaphore::new(total_kib as usize)),
+            total_kib,
+        }
+    }
+
+    /// Wait until a file of `bytes` fits in the budget. A file larger than
+    /// the whole budget takes the whole budget, so it's processed alone
+    async fn acquire(budget: Option<Self>, bytes: u64) -> Option<OwnedSemaphorePermit> {
+        let budget = budget?;
+        let kib = bytes.div_ceil(1024).clamp(1, budget.total_kib as u64) as u32;
+        budget.semaphore.acquire_many_owned(kib).await.ok()
+    }
+}
+
+/// Compute the unified diff of what fixing would change in the file at `path`
+fn fix_diff(
+    fs: &dyn FileSystem,
+    path: &Path,
+    holder: &Holders,
+    license: &str,
+    options: &NoticeOptions,
+) -> cu::Result<String> {
+    let original = fs.read_string(path)?;
+    let fixed = format::fixed_file_content_with_fs(fs, path, holder, license, options)?;
+    let path = path.display().to_string();
+    let diff = similar::TextDiff::from_lines(&original, &fixed)
+        .unified_diff()
+        .context_radius(3)
+        .header(&format!("a/{path}"), &format!("b/{path}"))
+        .to_string();
+    Ok(diff)
+}
+
+/// Error for a file that needs fixing but has uncommitted changes
+fn dirty_file_error(path: &Path) -> cu::Result<()> {
+    cu::bail!(
+        "'{}' has uncommitted changes and is not fixed. Commit or stash the changes first.",
+        path.display()
+    );
+}
+
+/// Expand the glob into a set of canonicalized paths for checking membership
+fn glob_path_set(glob: &str) -> cu::Result<BTreeSet<PathBuf>> {
+    let mut set = BTreeSet::new();
+    for glob in crate::expand_braces(glob) {
+        for path in cu::fs::glob(&glob)? {
+            set.insert(path?.canonicalize()?);
+        }
+    }
+    Ok(set)
+}
+
+/// Get the path segments matched by each whole-segment `*` in the glob,
+/// before any `**`. For example, `packages/*/src/**/*.rs` captures the package name
+fn glob_captures(glob: &str, path: &Path) -> cu::Result<Vec<String>> {
+    let glob_path = Path::new(glob);
+    // staged files are absolute
+    let glob_path = if path.is_absolute() && glob_path.is_relative() {
+        std::env::current_dir()?.join(glob_path)
+    } else {
+        glob_path.to_path_buf()
+    };
+    let mut captures = Vec::new();
+    for (pattern, component) in glob_path.components().zip(path.components()) {
+        let pattern = pattern.as_os_str();
+        if pattern == "**" {
+            break;
+        }
+        if pattern == "*" {
+            captures.push(component.as_os_str().to_string_lossy().into_owned());
+        }
+    }
+    Ok(captures)
+}
+
+/// Substitute `${N}` in the holder with the N-th capture (starting from 1) from
+/// the glob, see [`glob_captures`]. Returns `None` if the holder is not a template
+fn templated_holder(holder: &str, glob: &str, path: &Path) -> cu::Result<Option<String>> {
+    if !holder.contains("${") {
+        return Ok(None);
+    }
+    let captures = glob_captures(glob, path)?;
+    let mut out = String::with_capacity(holder.len());
+    let mut rest = holder;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            cu::bail!("unclosed '${{' in holder '{holder}'");
+        };
+        let index = &after[..end];
+        let capture = cu::parse::<usize>(index)
+            .ok()
+            .and_then(|i| captures.get(i.checked_sub(1)?));
+        let Some(capture) = capture else {
+            cu::bail!(
+                "'${{{index}}}' in holder '{holder}' does not refer to a '*' segment in the glob"
+            );
+        };
+        out.push_str(capture);
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    let out = out.trim();
+    if out.is_empty() {
+        cu::bail!("holder '{holder}' is empty after substitution");
+    }
+    Ok(Some(out.to_string()))
+}
+
+/// Substitute the templates in each holder, see [`templated_holder`].
+/// Returns `None` if none of the holders is a template
+fn templated_holders(holders: &Holders, glob: &str, path: &Path) -> cu::Result<Option<Holders>> {
+    let mut templated = false;
+    let mut out = Vec::with_capacity(holders.len());
+    for holder in holders.iter() {
+        match templated_holder(holder, glob, path)? {
+            Some(holder) => {
+                templated = true;
+                out.push(holder);
+            }
+            None => out.push(holder.to_string()),
+        }
+    }
+    Ok(templated.then(|| Holders::new(out)))
+}
+
 fn run_glob(
     glob: &str,
-    holder: Arc<String>,
+    literal: bool,
+    holder: Arc<Holders>,
     license: Arc<String>,
-    fix: bool,
-    pool: &cu::co::Pool,
-    handles: &mut Vec<cu::co::Handle<(PathBuf, cu::Result<()>)>>,
-    path_map: &mut BTreeMap<PathBuf, (Arc<String>, Arc<String>)>,
+    state: &mut RunState,
 ) -> cu::Result<bool> {
+    if let Some(listed) = &mut state.listed {
+        let (holder, license) = (Arc::clone(&holder), Arc::clone(&license));
+        listed.push((glob.to_string(), holder, license, Vec::new()));
+    }
+    let paths = if literal {
+        let path = PathBuf::from(glob);
+        // known files are not searched, but they must exist
+        cu::check!(state.fs.file_size(&path), "cannot find file '{glob}'")?;
+        match &state.staged {
+            Some(staged) if !staged.contains(&path)? => Vec::new(),
+            _ => vec![path],
+        }
+    } else {
+        match &state.staged {
+            Some(staged) => staged.matching(glob)?,
+            None => state.fs.glob(glob)?,
+        }
+    };
     let mut matched = false;
-    for path in cu::fs::glob(glob)? {
-        let path = path?;
-        if !path.is_file() {
+    for path in paths {
+        // excluded files still count as matched, since they are skipped on purpose
+        matched = true;
+        if state.excluded.contains(&normalize_path(path.clone())) {
+            cu::debug!("skipping excluded file '{}'", path.display());
             continue;
         }
-        matched = true;
-        let holder = Arc::clone(&holder);
+        if state.is_ignored(&path)? {
+            cu::debug!("skipping file ignored by git '{}'", path.display());
+            continue;
+        }
+        if let Some((_, _, _, files)) = state.listed.as_mut().and_then(|x| x.last_mut()) {
+            files.push(path);
+            continue;
+        }
+        // the holder can be different for each file if it's a template
+        let holder = match templated_holders(&holder, glob, &path)? {
+            Some(templated) => Arc::new(templated),
+            None => Arc::clone(&holder),
+        };
         let license = Arc::clone(&license);
+        let options = Arc::clone(&state.notice_options);
+        let fs = Arc::clone(&state.fs);
+        let budget = state.budget.clone();
+        let size = match &budget {
+            Some(_) => fs.file_size(&path)?,
+            None => 0,
+        };
 
+        let defer_write = state.defer_write;
+        let dry_run = state.dry_run;
+        let year_only = state.year_only;
+        let log_issues = state.log_issues;
+        let quiet = state.quiet;
+        let max_file_size = state.max_file_size;
+        let should_fix = state.should_fix(&path)?;
         // in fix mode, run additional check for if there are conflicts
         // in the config. Otherwise, the fix result is arbitrary
-        let handle = if fix {
-            use std::collections::btree_map::Entry;
-            match path_map.entry(path.clone()) {
-                Entry::Occupied(e) => {
-                    let (existing_h, existing_l) = e.get();
-                    if (existing_h, existing_l) != (&holder, &license) {
-                        cu::error!(
-                            "file '{}' matched by multiple globs of conflicting config!",
-                            e.key().display()
-                        );
-                        cu::error!(
-                            "- in one config, it has holder '{holder}' and license '{license}'"
-                        );
-                        cu::error!(
-                            "- in another, it has holder '{existing_h}' and license '{existing_l}'"
-                        );
-                        cu::bail!(
-                            "conflicting config found for '{}', while globbing '{glob}'",
-                            e.key().display()
-                        );
-                    }
-                    // since the file is already checked by previous job,
-                    // we can just skip it
-                    continue;
-                }
-                Entry::Vacant(e) => e.insert((Arc::clone(&holder), Arc::clone(&license))),
-            };
-            pool.spawn(async move {
-                let check_result = format::check_file(&path, &holder, &license);
+        if should_fix && !state.remove && !state.record_fix(&path, &holder, &license, glob)? {
+            // since the file is already checked by previous job,
+            // we can just skip it
+            continue;
+        }
+        let handle = if state.remove {
+            state.pool.spawn(async move {
+                let _permit = ByteBudget::acquire(budget, size).await;
+                if is_skipped(fs.as_ref(), &path, max_file_size) {
+                    return TaskOutput::skipped(path, holder);
+                }
+                let result = format::removed_file_content_with_fs(fs.as_ref(), &path, &options);
+                if let (Err(e), false) = (&result, quiet) {
+                    cu::error!("failed to remove notice from '{}': {e}", path.display());
+                }
+                TaskOutput {
+                    fixed: matches!(result, Ok(Some(_))),
+                    ..TaskOutput::new(path, holder, result)
+                }
+            })
+        } else if should_fix && state.is_dirty(&path)? {
+            state.pool.spawn(async move {
+                let _permit = ByteBudget::acquire(budget, size).await;
+                if is_skipped(fs.as_ref(), &path, max_file_size) {
+                    return TaskOutput::skipped(path, holder);
+                }
+                let Err(e) = format::check_file_with_fs(
+                    fs.as_ref(),
+                    &path,
+                    &holder,
+                    &license,
+                    &options.strict(),
+                ) else {
+                    return TaskOutput::new(path, holder, Ok(None));
+                };
+                cu::trace!("'{}': {e}", path.display());
+                let result = dirty_file_error(&path);
+                if let (Err(e), false) = (&result, quiet) {
+                    cu::error!("{e}");
+                }
+                TaskOutput::new(path, holder, result.map(|_| None))
+            })
+        } else if should_fix {
+            let new_year = state.new_notice_year(&path)?;
+            state.pool.spawn(async move {
+                let _permit = ByteBudget::acquire(budget, size).await;
+                if is_skipped(fs.as_ref(), &path, max_file_size) {
+                    return TaskOutput::skipped(path, holder);
+                }
+                // lenient comparisons are not used to determine if fix is needed,
+                // so the notice is normalized
+                let check_result = format::check_file_with_fs(
+                    fs.as_ref(),
+                    &path,
+                    &holder,
+                    &license,
+                    &options.strict(),
+                );
                 let Err(e) = check_result else {
-                    return (path, Ok(()));
+                    return TaskOutput::new(path, holder, Ok(None));
                 };
+                if year_only && e.downcast_ref::<format::StaleYear>().is_none() {
+                    if log_issues {
+                        cu::warn!("'{}': {e}", path.display());
+                    }
+                    return TaskOutput::new(path, holder, Err(e));
+                }
                 cu::trace!("'{}': {e}", path.display());
                 cu::debug!("fixing '{}'", path.display());
-                let Err(e) = format::fix_file(&path, &holder, &license) else {
-                    return (path, Ok(()));
+                let result = format::fixed_file_content_with_year(
+                    fs.as_ref(),
+                    &path,
+                    &holder,
+                    &license,
+                    &options,
+                    new_year,
+                );
+                // in a dry run, the issue is kept as the result
+                let would_fix = dry_run && result.is_ok();
+                let result = match result {
+                    Ok(_) if dry_run => {
+                        if log_issues {
+                            cu::info!("would fix '{}': {e}", path.display());
+                        }
+                        Err(e)
+                    }
+                    Ok(content) if !defer_write => fs.write(&path, &content).map(|_| None),
+                    result => result.map(Some),
                 };
-                cu::error!("failed to fix '{}': {e}", path.display());
-                (path, Err(e))
+                if let (Err(e), false) = (&result, quiet || would_fix) {
+                    cu::error!("failed to fix '{}': {e}", path.display());
+                }
+                TaskOutput {
+                    fixed: would_fix || result.is_ok(),
+                    ..TaskOutput::new(path, holder, result)
+                }
             })
         } else {
-            pool.spawn(async move {
-                let Err(e) = format::check_file(&path, &holder, &license) else {
-                    return (path, Ok(()));
+            let diffs = state.diffs.clone();
+            state.pool.spawn(async move {
+                let _permit = ByteBudget::acquire(budget, size).await;
+                if is_skipped(fs.as_ref(), &path, max_file_size) {
+                    return TaskOutput::skipped(path, holder);
+                }
+                let Err(e) =
+                    format::check_file_with_fs(fs.as_ref(), &path, &holder, &license, &options)
+                else {
+                    return TaskOutput::new(path, holder, Ok(None));
                 };
-                cu::warn!("'{}': {e}", path.display());
-                (path, Err(e))
+                if log_issues {
+                    cu::warn!("'{}': {e}", path.display());
+                }
+                if let Some(diffs) = diffs {
+                    match fix_diff(fs.as_ref(), &path, &holder, &license, &options) {
+                        Ok(diff) => {
+                            let mut diffs = diffs.lock().unwrap_or_else(|e| e.into_inner());
+                            diffs.insert(path.clone(), diff);
+                        }
+                        Err(e) => cu::debug!("cannot compute diff for '{}': {e}", path.display()),
+                    }
+                }
+                TaskOutput::new(path, holder, Err(e))
             })
         };
 
-        handles.push(handle);
+        state.handles.push(handle);
     }
 
     Ok(matched)
@@ -0,0 +1,202 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Pistonite
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use crate::{Config, is_supported};
+
+/// Directories that are skipped when walking the tree for suggestions,
+/// in addition to hidden directories
+static SKIP_DIRS: &[&str] = &["node_modules", "target"];
+
+/// Get the files under `root` that are not covered by any glob in `config`,
+/// as paths relative to `root`.
+///
+/// Hidden files and directories are skipped, along with `target` and `node_modules`.
+/// Files that cannot carry a license notice (see [`is_supported`]) are also skipped.
+pub fn uncovered_files(root: &Path, config: Option<&Config>) -> cu::Result<Vec<PathBuf>> {
+    uncovered_files_with_extensions(root, config, &[])
+}
+
+/// Same as [`uncovered_files`], but only files with one of the `extensions`
+/// (such as `rs`, without the dot) are considered. All extensions are considered if empty
+pub fn uncovered_files_with_extensions(
+    root: &Path,
+    config: Option<&Config>,
+    extensions: &[String],
+) -> cu::Result<Vec<PathBuf>> {
+    let root = root.canonicalize()?;
+    let covered = match config {
+        Some(config) => config.matched_paths()?,
+        None => BTreeSet::new(),
+    };
+    let mut files = Vec::new();
+    walk(&root, &mut files)?;
+
+    let mut uncovered = Vec::new();
+    for file in files {
+        if covered.contains(&file) || !is_supported(&file) {
+            continue;
+        }
+        if !extensions.is_empty() {
+            let extension = file
+                .extension()
+                .and_then(|x| x.to_str())
+                .unwrap_or_default();
+            if !extensions
+                .iter()
+                .any(|x| x.trim_start_matches('.') == extension)
+            {
+                continue;
+            }
+        }
+        if let Ok(rel_path) = file.strip_prefix(&root) {
+            uncovered.push(rel_path.to_path_buf());
+        }
+    }
+    uncovered.sort();
+    Ok(uncovered)
+}
+
+/// Suggest globs for the files under `root` that are not covered by `config`.
+///
+/// Files are grouped by their top-level directory and extension (or file name,
+/// if there's no extension). See [`uncovered_files`] for which files are considered.
+///
+/// Returns glob (relative to `root`) -> number of files covered by the glob
+pub fn suggest_globs(root: &Path, config: Option<&Config>) -> cu::Result<BTreeMap<String, usize>> {
+    let mut globs = BTreeMap::new();
+    for rel_path in uncovered_files(root, config)? {
+        *globs.entry(suggest_glob(&rel_path)).or_insert(0) += 1;
+    }
+    Ok(globs)
+}
+
+/// Suggest globs for the files under `root` with extensions that are not
+/// covered by `config` at all, for example when a new file type is added to the repo.
+///
+/// Each glob covers one extension anywhere under `root`. See [`uncovered_files`]
+/// for which files are considered.
+///
+/// Returns glob (relative to `root`) -> number of files covered by the glob
+pub fn suggest_new_extension_globs(
+    root: &Path,
+    config: &Config,
+) -> cu::Result<BTreeMap<String, usize>> {
+    let covered_extensions = config
+        .matched_paths()?
+        .into_iter()
+        .filter_map(|path| Some(path.extension()?.to_string_lossy().into_owned()))
+        .collect::<BTreeSet<_>>();
+    let mut globs = BTreeMap::new();
+    for rel_path in uncovered_files(root, Some(config))? {
+        let Some(ext) = rel_path.extension() else {
+            continue;
+        };
+        let ext = ext.to_string_lossy();
+        if covered_extensions.contains(ext.as_ref()) {
+            continue;
+        }
+        *globs.entry(format!("**/*.{ext}")).or_insert(0) += 1;
+    }
+    Ok(globs)
+}
+
+/// Render the suggested globs as a config table that can be pasted into `Lisensor.toml`
+pub fn suggest_config(
+    globs: &BTreeMap<String, usize>,
+    holder: &str,
+    license: &str,
+) -> cu::Result<String> {
+    use std::fmt::Write as _;
+    let mut out = String::new();
+    writeln!(out, "[{}]", toml_quote(holder))?;
+    out.push_str(&suggest_entries(globs, license)?);
+    Ok(out)
+}
+
+/// Add the suggested globs to the content of a config file, in the table
+/// for `holder`. A new table is added at the end if there isn't one
+pub fn extend_config(
+    content: &str,
+    globs: &BTreeMap<String, usize>,
+    holder: &str,
+    license: &str,
+) -> cu::Result<String> {
+    let entries = suggest_entries(globs, license)?;
+    let headers = [format!("[{holder}]"), format!("[{}]", toml_quote(holder))];
+    let mut out = String::with_capacity(content.len() + entries.len());
+    let mut inserted = false;
+    for line in content.lines() {
+        out.push_str(line);
+        out.push('\n');
+        if !inserted && headers.iter().any(|x| x == line.trim()) {
+            out.push_str(&entries);
+            inserted = true;
+        }
+    }
+    if !inserted {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&suggest_config(globs, holder, license)?);
+    }
+    Ok(out)
+}
+
+/// Render the suggested globs as entries in a holder table
+fn suggest_entries(globs: &BTreeMap<String, usize>, license: &str) -> cu::Result<String> {
+    use std::fmt::Write as _;
+    let mut out = String::new();
+    for (glob, count) in globs {
+        writeln!(
+            out,
+            "{} = {} # {count} file(s)",
+            toml_quote(glob),
+            toml_quote(license)
+        )?;
+    }
+    Ok(out)
+}
+
+fn suggest_glob(rel_path: &Path) -> String {
+    let file_pattern = match rel_path.extension() {
+        Some(ext) => format!("*.{}", ext.to_string_lossy()),
+        None => rel_path
+            .file_name()
+            .map(|x| x.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    };
+    let mut components = rel_path.iter();
+    match (components.next(), components.next()) {
+        // file is in a sub directory
+        (Some(dir), Some(_)) => format!("{}/**/{file_pattern}", dir.to_string_lossy()),
+        _ => file_pattern,
+    }
+}
+
+fn walk(dir: &Path, files: &mut Vec<PathBuf>) -> cu::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with('.') {
+            continue;
+        }
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            if !SKIP_DIRS.contains(&&*name) {
+                walk(&entry.path(), files)?;
+            }
+        } else if file_type.is_file() {
+            files.push(entry.path());
+        }
+    }
+    Ok(())
+}
+
+/// Quote the string as a TOML basic string
+fn toml_quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
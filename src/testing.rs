@@ -0,0 +1,97 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Pistonite
+
+//! Helpers for testing configs against golden outputs, similar to
+//! the fixture tests of this crate.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{Config, run};
+
+/// Output of running a fixture with [`run_fixture`]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FixtureOutput {
+    /// Errors in check mode, empty if the check passed
+    pub check_error: String,
+    /// Errors in fix mode, empty if the fix succeeded
+    pub fix_error: String,
+    /// Content of the file after fixing
+    pub fixed: String,
+}
+
+/// Run the check and the fix on a file named `file_name` with `content`,
+/// with the config created by `make_config` from the path of the file.
+///
+/// The file is created in a new temporary directory, which is removed
+/// afterwards. The extension of `file_name` determines the format of the notice.
+///
+/// This runs the coroutines internally, so it cannot be called from an async context.
+///
+/// ```
+/// # fn main() -> cu::Result<()> {
+/// use lisensor::Config;
+/// use lisensor::testing::run_fixture;
+///
+/// let output = run_fixture("main.rs", "fn main() {}\n", |path| {
+///     Config::new("Me".to_string(), "MIT".to_string(), vec![path])
+/// })?;
+/// assert_eq!(output.check_error, "missing license notice line.\n");
+/// assert_eq!(output.fix_error, "");
+/// assert!(output.fixed.starts_with("// SPDX-License-Identifier: MIT\n"));
+/// assert!(output.fixed.ends_with("\n\nfn main() {}\n"));
+/// # Ok(())
+/// # }
+/// ```
+pub fn run_fixture(
+    file_name: &str,
+    content: &str,
+    make_config: impl FnOnce(String) -> Config,
+) -> cu::Result<FixtureOutput> {
+    let dir = new_temp_dir()?;
+    let result = run_fixture_in(&dir, file_name, content, make_config);
+    if let Err(e) = std::fs::remove_dir_all(&dir) {
+        cu::warn!("failed to remove '{}': {e}", dir.display());
+    }
+    result
+}
+
+fn run_fixture_in(
+    dir: &std::path::Path,
+    file_name: &str,
+    content: &str,
+    make_config: impl FnOnce(String) -> Config,
+) -> cu::Result<FixtureOutput> {
+    let path = dir.join(file_name);
+    cu::fs::write(&path, content)?;
+    let config = make_config(path.to_string_lossy().into_owned());
+    let config2 = config.clone();
+
+    let check_error = match cu::co::run(async move { run(config, false).await })? {
+        Ok(()) => String::new(),
+        Err(e) => e.to_string(),
+    };
+    let fix_error = match cu::co::run(async move { run(config2, true).await })? {
+        Ok(()) => String::new(),
+        Err(e) => e.to_string(),
+    };
+    let fixed = cu::fs::read_string(&path)?;
+
+    Ok(FixtureOutput {
+        check_error,
+        fix_error,
+        fixed,
+    })
+}
+
+/// Create a new directory in the system temporary directory
+fn new_temp_dir() -> cu::Result<PathBuf> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let dir = std::env::temp_dir().join(format!("lisensor-fixture-{}-{n}", std::process::id()));
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir)?;
+    }
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
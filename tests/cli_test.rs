@@ -0,0 +1,148 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Pistonite
+
+#![cfg(feature = "cli")]
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use clap_complete::Shell;
+use cu::pre::clap::Parser as _;
+use lisensor::{Cli, EXIT_ISSUES, check_config, config_from_cli, generate_completions, run_stdin};
+
+#[test]
+fn completions_for_all_shells() {
+    for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell] {
+        let mut out = Vec::new();
+        generate_completions(shell, &mut out);
+        let script = String::from_utf8(out).unwrap();
+        assert!(script.contains("lisensor"), "no completions for {shell}");
+        assert!(script.contains("only-path"), "missing flags for {shell}");
+    }
+}
+
+#[test]
+fn stdin_mode() -> cu::Result<()> {
+    cu::cli::level("qq");
+    let args = [
+        "lisensor",
+        "--stdin",
+        "--path",
+        "a.py",
+        "-H",
+        "TestHolder",
+        "-L",
+        "TestLicense",
+    ];
+    let content = "print('hi')\n";
+
+    let mut cli = Cli::try_parse_from(args)?;
+    let mut out = Vec::new();
+    assert!(run_stdin(&mut cli, &mut content.as_bytes(), &mut out)?.is_err());
+    assert_eq!(String::from_utf8(out)?, content);
+
+    let mut cli = Cli::try_parse_from(args.iter().chain(&["--fix"]))?;
+    let mut out = Vec::new();
+    run_stdin(&mut cli, &mut content.as_bytes(), &mut out)?.unwrap();
+    let fixed = String::from_utf8(out)?;
+    assert!(fixed.starts_with("# SPDX-License-Identifier: TestLicense\n# Copyright (c) "));
+    assert!(fixed.ends_with(" TestHolder\n\nprint('hi')\n"));
+
+    let mut cli = Cli::try_parse_from(args)?;
+    let mut out = Vec::new();
+    run_stdin(&mut cli, &mut fixed.as_bytes(), &mut out)?.unwrap();
+    assert_eq!(String::from_utf8(out)?, fixed);
+
+    assert!(Cli::try_parse_from(["lisensor", "--stdin", "-H", "A", "-L", "B"]).is_err());
+    Ok(())
+}
+
+#[test]
+fn stdin_exit_code() -> cu::Result<()> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_lisensor"))
+        .args([
+            "--stdin",
+            "--path",
+            "a.py",
+            "-H",
+            "TestHolder",
+            "-L",
+            "TestLicense",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(b"print('hi')\n")?;
+    drop(stdin);
+    let output = child.wait_with_output()?;
+    assert_eq!(output.status.code(), Some(EXIT_ISSUES.into()));
+    assert_eq!(String::from_utf8(output.stdout)?, "print('hi')\n");
+    Ok(())
+}
+
+#[test]
+fn stdin_pinned_year() -> cu::Result<()> {
+    cu::cli::level("qq");
+    let args = [
+        "lisensor",
+        "--stdin",
+        "--path",
+        "a.rs",
+        "-H",
+        "TestHolder",
+        "-L",
+        "TestLicense",
+        "--year",
+        "2030",
+    ];
+    let content = "// SPDX-License-Identifier: TestLicense\n// Copyright (c) 2020 TestHolder\n";
+
+    let mut cli = Cli::try_parse_from(args)?;
+    let mut out = Vec::new();
+    assert!(run_stdin(&mut cli, &mut content.as_bytes(), &mut out)?.is_err());
+
+    let mut cli = Cli::try_parse_from(args.iter().chain(&["--fix"]))?;
+    let mut out = Vec::new();
+    run_stdin(&mut cli, &mut content.as_bytes(), &mut out)?.unwrap();
+    let fixed = String::from_utf8(out)?;
+    assert_eq!(
+        fixed,
+        "// SPDX-License-Identifier: TestLicense\n// Copyright (c) 2020-2030 TestHolder\n"
+    );
+
+    let mut cli = Cli::try_parse_from(args)?;
+    let mut out = Vec::new();
+    run_stdin(&mut cli, &mut fixed.as_bytes(), &mut out)?.unwrap();
+    assert_eq!(String::from_utf8(out)?, fixed);
+    Ok(())
+}
+
+#[test]
+fn check_config_only() -> cu::Result<()> {
+    cu::cli::level("qq");
+    let mut cli = Cli::try_parse_from([
+        "lisensor",
+        "--check-config",
+        "tests/configs/conflict/lisensor.toml",
+    ])?;
+    assert!(config_from_cli(&mut cli).is_err());
+
+    let mut cli = Cli::try_parse_from([
+        "lisensor",
+        "--check-config",
+        "tests/configs/holder_file/Lisensor.toml",
+    ])?;
+    let config = config_from_cli(&mut cli)?;
+    let mut out = Vec::new();
+    check_config(&config, &mut out)?;
+    let out = String::from_utf8(out)?;
+    assert!(
+        out.contains("(holder 'Foobar Contributors Incorporated', license 'MIT')"),
+        "{out}"
+    );
+
+    assert!(Cli::try_parse_from(["lisensor", "--check-config", "--fix"]).is_err());
+    Ok(())
+}
@@ -0,0 +1,40 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Pistonite
+
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+
+use lisensor::Config;
+
+/// Create a clean directory with the files for the test
+pub fn setup(name: &str, files: &[(&str, &str)]) -> cu::Result<PathBuf> {
+    cu::cli::level("qq");
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir)?;
+    }
+    std::fs::create_dir_all(&dir)?;
+    for (file, content) in files {
+        let path = dir.join(file);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, content)?;
+    }
+    Ok(dir)
+}
+
+/// Make a glob string with the pattern relative to `dir`
+pub fn glob(dir: &Path, pattern: &str) -> String {
+    dir.join(pattern).to_string_lossy().into_owned()
+}
+
+/// Make a config with the test holder and license for the pattern relative to `dir`
+pub fn test_config(dir: &Path, pattern: &str) -> Config {
+    Config::new(
+        "TestHolder".to_string(),
+        "TestLicense".to_string(),
+        vec![glob(dir, pattern)],
+    )
+}
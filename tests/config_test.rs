@@ -0,0 +1,240 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Pistonite
+
+use std::path::Path;
+
+use lisensor::{Config, Format, Holders, Resolution};
+
+#[test]
+fn holder_from_file() -> cu::Result<()> {
+    let config = Config::build("tests/configs/holder_file/Lisensor.toml")?;
+    let expected = Config::new(
+        "Foobar Contributors Incorporated".to_string(),
+        "MIT".to_string(),
+        vec!["tests/configs/holder_file/src/**/*.rs".to_string()],
+    );
+    assert_eq!(config, expected);
+    Ok(())
+}
+
+#[test]
+fn holder_file_missing() {
+    assert!(Config::build("tests/configs/holder_file_missing.toml").is_err());
+}
+
+#[test]
+fn json_and_yaml_configs() -> cu::Result<()> {
+    let expected = Config::build("tests/configs/formats/lisensor.toml")?;
+    assert_eq!(expected.notice_options().min_year, Some(2000));
+    assert_eq!(
+        expected.notice_options().format_for(Path::new("a.conf")),
+        Format::Hash
+    );
+    for path in [
+        "tests/configs/formats/lisensor.json",
+        "tests/configs/formats/lisensor.yaml",
+    ] {
+        assert_eq!(Config::build(path)?, expected, "config mismatch for {path}");
+    }
+    let error = Config::build("tests/configs/formats/lisensor.ini").unwrap_err();
+    assert!(error.to_string().contains("unrecognized config file"));
+    Ok(())
+}
+
+#[test]
+fn extends_base_config() -> cu::Result<()> {
+    let config = Config::build("tests/configs/extends/pkg/lisensor.toml")?;
+    let mut expected = Config::new(
+        "Foobar".to_string(),
+        "MIT".to_string(),
+        vec![
+            // inherited globs are relative to the base config
+            "tests/configs/extends/pkg/../src/**/*.rs".to_string(),
+            "tests/configs/extends/pkg/src/**/*.py".to_string(),
+        ],
+    );
+    expected.notice_options_mut().min_year = Some(2000);
+    assert_eq!(config, expected);
+    Ok(())
+}
+
+#[test]
+fn extends_with_options() -> cu::Result<()> {
+    // options from the base are kept, and new options are added
+    let config = Config::build("tests/configs/extends/override/lisensor.toml")?;
+    let options = config.notice_options();
+    assert_eq!(options.min_year, Some(2000));
+    assert!(options.allow_stale_year);
+
+    // options in the config override the ones in the base
+    let config = Config::build("tests/configs/extends/override/min_year.toml")?;
+    assert_eq!(config.notice_options().min_year, Some(2010));
+
+    // sibling configs only conflict when they set an option to different values
+    let mut config = Config::default();
+    config.notice_options_mut().min_year = Some(2000);
+    let mut incoming = Config::default();
+    incoming.notice_options_mut().allow_stale_year = true;
+    config.absorb(incoming)?;
+    assert_eq!(config.notice_options().min_year, Some(2000));
+    assert!(config.notice_options().allow_stale_year);
+    let mut incoming = Config::default();
+    incoming.notice_options_mut().min_year = Some(2010);
+    assert!(config.absorb(incoming).is_err());
+    Ok(())
+}
+
+#[test]
+fn extends_cycle() {
+    let error = Config::build("tests/configs/extends/cycle_a.toml").unwrap_err();
+    assert!(format!("{error:?}").contains("cyclic extends detected"));
+}
+
+#[test]
+fn format_overrides() -> cu::Result<()> {
+    let content = r#"
+[format]
+conf = "hash"
+js = "block"
+
+[Foo]
+"*.conf" = "MIT"
+"#;
+    let config = Config::parse(content, Path::new(""), "Lisensor.toml")?;
+    let options = config.notice_options();
+    assert_eq!(options.format_for(Path::new("a.conf")), Format::Hash);
+    assert_eq!(options.format_for(Path::new("a.js")), Format::Block);
+    // extensions not in the table use the built-in defaults
+    assert_eq!(options.format_for(Path::new("a.py")), Format::Hash);
+    assert_eq!(options.format_for(Path::new("a.rs")), Format::SlashSlash);
+
+    let config = Config::parse(
+        "[Foo]\n\"*.js\" = \"MIT\"\n",
+        Path::new(""),
+        "Lisensor.toml",
+    )?;
+    assert_eq!(
+        config.notice_options().format_for(Path::new("a.js")),
+        Format::SlashSlash
+    );
+    Ok(())
+}
+
+#[test]
+fn license_aliases() -> cu::Result<()> {
+    let content = "[aliases]\nApache2 = \"Apache-2.0\"\n\n[Foo]\n\"*.rs\" = \"Apache2\"\n\"*.js\" = \"MIT\"\n";
+    let config = Config::parse(content, Path::new(""), "Lisensor.toml")?;
+    let content = "[Foo]\n\"*.rs\" = \"Apache-2.0\"\n\"*.js\" = \"MIT\"\n";
+    let expected = Config::parse(content, Path::new(""), "Lisensor.toml")?;
+    assert_eq!(config, expected);
+
+    let content = "[aliases]\nApache = \"Apache2\"\nApache2 = \"Apache-2.0\"\n";
+    assert!(Config::parse(content, Path::new(""), "Lisensor.toml").is_err());
+
+    // invalid expressions are rejected with or without the spdx feature
+    for license in ["Apache 2.0", "MIT OR", "(MIT"] {
+        let content = format!("[aliases]\nApache2 = \"{license}\"\n");
+        assert!(Config::parse(&content, Path::new(""), "Lisensor.toml").is_err());
+    }
+    Ok(())
+}
+
+#[test]
+fn multi_holder() -> cu::Result<()> {
+    let content = r#"
+["Foo\nBar"]
+"*.rs" = "MIT"
+
+[multi_holder."*.js"]
+holders = ["Foo", "Bar"]
+license = "MIT"
+"#;
+    let config = Config::parse(content, Path::new(""), "Lisensor.toml")?;
+    let entries = config
+        .into_iter()
+        .map(|(glob, holders, _)| (glob, holders))
+        .collect::<Vec<_>>();
+    let expected_js = Holders::new(vec!["Foo".to_string(), "Bar".to_string()]);
+    assert_eq!(entries[0].0, "*.js");
+    assert_eq!(*entries[0].1, expected_js);
+    assert_eq!(expected_js.to_string(), "'Foo', 'Bar'");
+    // a holder with a line break is still one holder
+    assert_eq!(entries[1].0, "*.rs");
+    assert_eq!(entries[1].1.as_slice(), ["Foo\nBar"]);
+    Ok(())
+}
+
+#[test]
+fn format_unknown() {
+    let content = "[format]\nconf = \"semicolon\"\n";
+    let error = Config::parse(content, Path::new(""), "Lisensor.toml").unwrap_err();
+    assert!(error.to_string().contains("unknown format 'semicolon'"));
+}
+
+fn conflicting_configs() -> (Config, Config) {
+    let existing = Config::new(
+        "Existing".to_string(),
+        "MIT".to_string(),
+        vec!["a/*.rs".to_string(), "b/*.rs".to_string()],
+    );
+    let incoming = Config::new(
+        "Incoming".to_string(),
+        "MIT".to_string(),
+        vec!["b/*.rs".to_string(), "c/*.rs".to_string()],
+    );
+    (existing, incoming)
+}
+
+fn holders(config: Config) -> Vec<(String, String)> {
+    config
+        .into_iter()
+        .map(|(glob, holder, _)| (glob, holder.as_slice().join(", ")))
+        .collect()
+}
+
+#[test]
+fn absorb_with_keep_existing() -> cu::Result<()> {
+    let (mut config, incoming) = conflicting_configs();
+    config.absorb_with(incoming, |glob, existing, incoming| {
+        assert_eq!(glob, "b/*.rs");
+        assert_eq!(existing.holder.as_slice(), ["Existing"]);
+        assert_eq!(incoming.holder.as_slice(), ["Incoming"]);
+        Resolution::KeepExisting
+    })?;
+    assert_eq!(
+        holders(config),
+        vec![
+            ("a/*.rs".to_string(), "Existing".to_string()),
+            ("b/*.rs".to_string(), "Existing".to_string()),
+            ("c/*.rs".to_string(), "Incoming".to_string()),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn absorb_with_take_incoming() -> cu::Result<()> {
+    let (mut config, incoming) = conflicting_configs();
+    config.absorb_with(incoming, |_, _, _| Resolution::TakeIncoming)?;
+    assert_eq!(
+        holders(config),
+        vec![
+            ("a/*.rs".to_string(), "Existing".to_string()),
+            ("b/*.rs".to_string(), "Incoming".to_string()),
+            ("c/*.rs".to_string(), "Incoming".to_string()),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn absorb_with_error() {
+    let (mut config, incoming) = conflicting_configs();
+    assert!(
+        config
+            .absorb_with(incoming, |_, _, _| Resolution::Error)
+            .is_err()
+    );
+    let (mut config, incoming) = conflicting_configs();
+    assert!(config.absorb(incoming).is_err());
+}
@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Pistonite
+
+#![cfg(feature = "cli")]
+
+mod common;
+use common::*;
+
+use cu::pre::clap::Parser as _;
+use lisensor::{Cli, HOLDER_ENV, LICENSE_ENV, config_from_cli};
+
+#[test]
+fn inline_config_from_env() -> cu::Result<()> {
+    let dir = setup("inline_config_from_env", &[("a.rs", "fn a() {}\n")])?;
+    // the default config file is searched in the current directory
+    std::env::set_current_dir(&dir)?;
+    // SAFETY: this is the only test in this binary
+    unsafe {
+        std::env::set_var(HOLDER_ENV, "EnvHolder");
+        std::env::set_var(LICENSE_ENV, "EnvLicense");
+    }
+
+    let mut cli = Cli::try_parse_from(["lisensor", "*.rs"])?;
+    let entries = config_from_cli(&mut cli)?.into_iter().collect::<Vec<_>>();
+    assert_eq!(entries.len(), 1);
+    let (glob, holder, license) = &entries[0];
+    assert_eq!(glob, "*.rs");
+    assert_eq!(holder.as_slice(), ["EnvHolder"]);
+    assert_eq!(license.as_str(), "EnvLicense");
+
+    // flags take precedence over the environment variables
+    let mut cli = Cli::try_parse_from(["lisensor", "-H", "A", "-L", "B", "*.rs"])?;
+    let entries = config_from_cli(&mut cli)?.into_iter().collect::<Vec<_>>();
+    assert_eq!(entries[0].1.as_slice(), ["A"]);
+    assert_eq!(entries[0].2.as_str(), "B");
+
+    // inline config cannot be mixed with the config file
+    std::fs::write(
+        dir.join("Lisensor.toml"),
+        "[TestHolder]\n\"*.rs\" = \"MIT\"\n",
+    )?;
+    let mut cli = Cli::try_parse_from(["lisensor", "*.rs"])?;
+    assert!(config_from_cli(&mut cli).is_err());
+
+    std::fs::remove_file(dir.join("Lisensor.toml"))?;
+
+    // config files are not silently used as globs
+    let mut cli = Cli::try_parse_from(["lisensor", "configs/lisensor.yml"])?;
+    assert!(config_from_cli(&mut cli).is_err());
+    let mut cli = Cli::try_parse_from(["lisensor", "*.toml"])?;
+    assert!(config_from_cli(&mut cli).is_ok());
+    let args = ["lisensor", "-H", "A", "-L", "B", "Cargo.toml"];
+    let mut cli = Cli::try_parse_from(args)?;
+    assert!(config_from_cli(&mut cli).is_ok());
+
+    // SAFETY: see above
+    unsafe {
+        std::env::remove_var(LICENSE_ENV);
+    }
+    let mut cli = Cli::try_parse_from(["lisensor", "*.rs"])?;
+    assert!(config_from_cli(&mut cli).is_err());
+    Ok(())
+}
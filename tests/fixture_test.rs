@@ -3,9 +3,77 @@
 
 use std::path::Path;
 
-use lisensor::{Config, run};
+use lisensor::{
+    Config, CopyrightStyle, Declarations, Format, Holders, RunOptions, run, run_with_options,
+};
+
+/// Create the default config for the fixture at `path`
+pub fn default_config(path: String) -> Config {
+    Config::new(
+        "TestHolder".to_string(),
+        "TestLicense".to_string(),
+        vec![path],
+    )
+}
+
+/// Create the config with the email of the holder for the fixture at `path`
+pub fn email_config(path: String) -> Config {
+    Config::new(
+        "TestHolder <test@example.com>".to_string(),
+        "TestLicense".to_string(),
+        vec![path],
+    )
+}
+
+/// Create the config with two holders for the fixture at `path`
+pub fn two_holders_config(path: String) -> Config {
+    Config::from_holders(
+        Holders::new(vec!["HolderA".to_string(), "HolderB".to_string()]),
+        "TestLicense".to_string(),
+        vec![path],
+    )
+}
+
+/// Create the config with the unicode copyright style for the fixture at `path`
+pub fn unicode_config(path: String) -> Config {
+    let mut config = default_config(path);
+    config.notice_options_mut().copyright_style = CopyrightStyle::Unicode;
+    config
+}
+
+/// Create the config with `// @generated` and vim modelines as preamble for the fixture at `path`
+pub fn preamble_config(path: String) -> Config {
+    let mut config = default_config(path);
+    config.notice_options_mut().preamble = vec!["// @generated".to_string(), "// vim:".to_string()];
+    config
+}
+
+/// Create the config with `package` declarations in java files for the fixture at `path`
+pub fn package_config(path: String) -> Config {
+    let mut config = default_config(path);
+    let declarations = Declarations {
+        prefixes: vec!["package ".to_string()],
+        max_lines: 1,
+    };
+    config
+        .notice_options_mut()
+        .declarations
+        .insert("java".to_string(), declarations);
+    config
+}
+
+/// Header template for the fixtures with a full header
+const HEADER_TEMPLATE: &str = "Copyright {year} {holder}
+Licensed under the {license} license.
+See the LICENSE file for details.
+";
 
 pub fn run_fixture(name: &str) -> cu::Result<()> {
+    run_fixture_with(name, default_config)
+}
+
+/// Run the fixture with the config created by `make_config` from the path of the file
+pub fn run_fixture_with(name: &str, make_config: impl FnOnce(String) -> Config) -> cu::Result<()> {
     cu::cli::level("qq");
     let update_output = std::env::var("FIXTURE_UPDATE").unwrap_or_default().as_str() == "1";
 
@@ -14,14 +82,20 @@ pub fn run_fixture(name: &str) -> cu::Result<()> {
 
     cu::debug!("running fixture: {name}");
 
-    let input_copy_path = fixtures.join(format!("{name}_out"));
+    // keep the extension (or the name of files without one),
+    // since it determines the format of the notice
+    let input_copy_path = match name.rsplit_once('.') {
+        Some((stem, ext)) if ext != "txt" => fixtures.join(format!("{stem}_out.{ext}")),
+        Some(_) => fixtures.join(format!("{name}_out")),
+        None => {
+            let dir = fixtures.join(format!("{name}_out"));
+            std::fs::create_dir_all(&dir)?;
+            dir.join(name)
+        }
+    };
     std::fs::copy(&input_path, &input_copy_path)?;
 
-    let config = Config::new(
-        "TestHolder".to_string(),
-        "TestLicense".to_string(),
-        vec![input_copy_path.to_string_lossy().into_owned()],
-    );
+    let config = make_config(input_copy_path.to_string_lossy().into_owned());
     let config2 = config.clone();
 
     let check_result = cu::co::run(async move { run(config, false).await })?;
@@ -105,6 +179,52 @@ pub fn run_fixture(name: &str) -> cu::Result<()> {
     Ok(())
 }
 
+/// Run the fixture in remove mode, and compare the output with `{name}_removed`
+pub fn run_remove_fixture(name: &str) -> cu::Result<()> {
+    cu::cli::level("qq");
+    let update_output = std::env::var("FIXTURE_UPDATE").unwrap_or_default().as_str() == "1";
+
+    let fixtures = Path::new("tests").join("fixtures");
+    let input_path = fixtures.join(name);
+    let input_copy_path = fixtures.join(format!("{name}_out"));
+    std::fs::copy(&input_path, &input_copy_path)?;
+
+    let config = default_config(input_copy_path.to_string_lossy().into_owned());
+    let options = RunOptions {
+        remove: true,
+        ..Default::default()
+    };
+    let result = cu::co::run(async move { run_with_options(config, options).await })?;
+    if let Err(e) = result {
+        cu::bail!("fixture '{name}' failed in remove mode: {e}");
+    }
+
+    let expected_output = fixtures.join(format!("{name}_removed"));
+    let actual_output_content = cu::fs::read_string(&input_copy_path)?;
+    if !expected_output.exists() {
+        cu::fs::write(expected_output, actual_output_content)?;
+        return Ok(());
+    }
+    let expected_output_content = cu::fs::read_string(&expected_output)?;
+    if expected_output_content != actual_output_content {
+        if update_output {
+            cu::fs::write(expected_output, actual_output_content)?;
+        } else {
+            cu::bail!("fixture '{name}' output mismatch. actual:\n{actual_output_content}");
+        }
+    }
+    Ok(())
+}
+
+macro_rules! remove_fixture {
+    ($name:ident) => {
+        #[test]
+        fn $name() -> cu::Result<()> {
+            run_remove_fixture(concat!(stringify!($name), ".txt"))
+        }
+    };
+}
+
 macro_rules! run_fixture {
     ($name:ident) => {
         #[test]
@@ -112,6 +232,33 @@ macro_rules! run_fixture {
             run_fixture(concat!(stringify!($name), ".txt"))
         }
     };
+    ($name:ident, $make_config:expr) => {
+        #[test]
+        fn $name() -> cu::Result<()> {
+            run_fixture_with(concat!(stringify!($name), ".txt"), $make_config)
+        }
+    };
+    ($name:ident . $ext:ident) => {
+        #[test]
+        fn $name() -> cu::Result<()> {
+            run_fixture(concat!(stringify!($name), ".", stringify!($ext)))
+        }
+    };
+    ($name:ident = $file:literal) => {
+        #[test]
+        fn $name() -> cu::Result<()> {
+            run_fixture($file)
+        }
+    };
+    ($name:ident . $ext:ident, $make_config:expr) => {
+        #[test]
+        fn $name() -> cu::Result<()> {
+            run_fixture_with(
+                concat!(stringify!($name), ".", stringify!($ext)),
+                $make_config,
+            )
+        }
+    };
 }
 
 run_fixture!(empty_text);
@@ -119,14 +266,160 @@ run_fixture!(only_1line);
 run_fixture!(wrong_license);
 run_fixture!(wrong_holder);
 run_fixture!(wrong_holder_license);
+run_fixture!(extra_blank_lines);
 run_fixture!(after_copyright);
 run_fixture!(before_license);
 run_fixture!(middle_license);
 run_fixture!(multi_correct);
 run_fixture!(multi_wrong);
+run_fixture!(duplicate_notice);
 run_fixture!(sentinel_first);
 run_fixture!(wrong_year);
 run_fixture!(wrong_year_future);
 run_fixture!(wrong_year_future_range);
 run_fixture!(wrong_year_range);
+run_fixture!(inverted_range);
 run_fixture!(first_not_sentinel);
+run_fixture!(stray_cr);
+run_fixture!(smart_quote_holder, |path| {
+    let mut config = Config::new(
+        "TestHolder's Company".to_string(),
+        "TestLicense".to_string(),
+        vec![path],
+    );
+    config.notice_options_mut().normalize_typography = true;
+    config
+});
+run_fixture!(terraform.tf);
+run_fixture!(ocaml.ml);
+run_fixture!(markdown_code_block.md);
+run_fixture!(mixed_style.py);
+run_fixture!(wrong_format.py);
+run_fixture!(dockerfile = "Dockerfile");
+run_fixture!(shebang_no_extension = "shebang_script");
+run_fixture!(block_correct.css);
+run_fixture!(block_missing.c);
+run_fixture!(xml_declaration.xml);
+run_fixture!(html_notice_first.html);
+run_fixture!(shebang_missing);
+run_fixture!(shebang_present);
+run_fixture!(shebang_only);
+run_fixture!(crlf_missing);
+run_fixture!(no_trailing_newline);
+run_fixture!(no_trailing_newline_1line);
+run_fixture!(terraform_slash.tf, |path| {
+    let mut config = default_config(path);
+    config
+        .notice_options_mut()
+        .formats
+        .insert("tf".to_string(), Format::SlashSlash);
+    config
+});
+run_fixture!(case_mismatch_holder, |path| {
+    let mut config = default_config(path);
+    config.notice_options_mut().ignore_case_holder = true;
+    config
+});
+run_fixture!(no_blank_after.toml, |path| {
+    let mut config = default_config(path);
+    config
+        .notice_options_mut()
+        .blank_after
+        .insert("toml".to_string(), false);
+    config
+});
+run_fixture!(file_type_tag, |path| {
+    let mut config = default_config(path);
+    config
+        .notice_options_mut()
+        .tags_before_license
+        .push("SPDX-FileType: SOURCE".to_string());
+    config
+});
+run_fixture!(file_type_tag_after, |path| {
+    let mut config = default_config(path);
+    config
+        .notice_options_mut()
+        .tags_after_license
+        .push("SPDX-FileType: SOURCE".to_string());
+    config
+});
+run_fixture!(header_missing, |path| {
+    let mut config = default_config(path);
+    config
+        .notice_options_mut()
+        .headers
+        .insert("TestHolder".to_string(), HEADER_TEMPLATE.to_string());
+    config
+});
+run_fixture!(header_from_spdx, |path| {
+    let mut config = default_config(path);
+    config
+        .notice_options_mut()
+        .headers
+        .insert("TestHolder".to_string(), HEADER_TEMPLATE.to_string());
+    config
+});
+run_fixture!(year_too_old, |path| {
+    let mut config = default_config(path);
+    config.notice_options_mut().min_year = Some(1970);
+    config
+});
+run_fixture!(extra_blank_lines_after, |path| {
+    let mut config = default_config(path);
+    config.notice_options_mut().max_blank_lines_after = Some(1);
+    config
+});
+run_fixture!(email_match, email_config);
+run_fixture!(email_missing, email_config);
+run_fixture!(email_wrong, email_config);
+run_fixture!(email_optional);
+run_fixture!(present_year);
+run_fixture!(spacing_none);
+run_fixture!(spacing_double);
+run_fixture!(unicode_style, unicode_config);
+run_fixture!(unicode_missing, unicode_config);
+run_fixture!(unicode_to_c);
+run_fixture!(bom_missing);
+run_fixture!(bom_correct);
+run_fixture!(bom_only);
+run_fixture!(two_holders, two_holders_config);
+run_fixture!(two_holders_missing, two_holders_config);
+run_fixture!(mixed_style_two_holders.py, two_holders_config);
+run_fixture!(wrong_format_two_holders.py, two_holders_config);
+run_fixture!(stale_year_allowed, |path| {
+    let mut config = default_config(path);
+    config.notice_options_mut().allow_stale_year = true;
+    config
+});
+run_fixture!(stale_year_strict);
+run_fixture!(present_fix, |path| {
+    let mut config = default_config(path);
+    config.notice_options_mut().write_present = true;
+    config
+});
+run_fixture!(preamble_generated, preamble_config);
+run_fixture!(preamble_blank, preamble_config);
+run_fixture!(ignore_marker);
+run_fixture!(ignore_marker_hash.py);
+run_fixture!(ignore_not_first.py);
+run_fixture!(package_first.java, package_config);
+run_fixture!(package_missing.java, package_config);
+run_fixture!(license_reordered, |path| {
+    Config::new(
+        "TestHolder".to_string(),
+        "Apache-2.0 OR MIT".to_string(),
+        vec![path],
+    )
+});
+run_fixture!(license_reordered_nested, |path| {
+    Config::new(
+        "TestHolder".to_string(),
+        "(MIT AND BSD-3-Clause) OR Apache-2.0".to_string(),
+        vec![path],
+    )
+});
+
+remove_fixture!(remove_clean);
+remove_fixture!(remove_sentinel);
+remove_fixture!(remove_none);
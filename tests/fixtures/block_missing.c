@@ -0,0 +1,4 @@
+/* * * * * * generated below */
+int main(void) {
+    return 0;
+}
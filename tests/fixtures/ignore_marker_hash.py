@@ -0,0 +1,2 @@
+# lisensor:ignore
+print("hi")
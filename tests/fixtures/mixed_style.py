@@ -0,0 +1,4 @@
+# SPDX-License-Identifier: TestLicense
+// Copyright (c) 2026 TestHolder
+
+print("hello")
@@ -0,0 +1,5 @@
+package com.foo;
+// SPDX-License-Identifier: TestLicense
+// Copyright (c) 2026 TestHolder
+
+class A {}
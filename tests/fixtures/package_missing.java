@@ -0,0 +1,5 @@
+package com.foo;
+
+import java.util.List;
+
+class A {}
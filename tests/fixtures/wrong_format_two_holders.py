@@ -0,0 +1,5 @@
+// SPDX-License-Identifier: TestLicense
+// Copyright (c) 2020-2026 HolderA
+// Copyright (c) 2026 HolderB
+
+print("hello")
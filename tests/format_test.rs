@@ -0,0 +1,78 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Pistonite
+
+mod common;
+use common::*;
+
+use lisensor::{Format, NoticeOptions, check_file};
+
+#[test]
+fn check_stops_at_max_scan_lines() -> cu::Result<()> {
+    let dir = setup("check_stops_at_max_scan_lines", &[])?;
+    let path = dir.join("large.rs");
+    let mut content =
+        b"// SPDX-License-Identifier: TestLicense\n// Copyright (c) 2026 TestHolder\n".to_vec();
+    content.extend(b"\n".repeat(100_000));
+    // reading this line would fail
+    content.extend(b"\xff\xfe\n");
+    std::fs::write(&path, content)?;
+
+    let mut options = NoticeOptions {
+        max_blank_lines_after: Some(usize::MAX),
+        max_scan_lines: Some(10),
+        ..Default::default()
+    };
+    check_file(&path, &"TestHolder".into(), "TestLicense", &options)?;
+
+    options.max_scan_lines = Some(usize::MAX);
+    assert!(check_file(&path, &"TestHolder".into(), "TestLicense", &options).is_err());
+    Ok(())
+}
+
+#[test]
+fn check_stops_at_max_scan_bytes() -> cu::Result<()> {
+    let notice = "// SPDX-License-Identifier: TestLicense\n// Copyright (c) 2026 TestHolder\n";
+    let minified = "x".repeat(100_000);
+    let dir = setup(
+        "check_stops_at_max_scan_bytes",
+        &[
+            ("notice.js", format!("{notice}\n{minified}").as_str()),
+            ("minified.js", minified.as_str()),
+        ],
+    )?;
+    let options = NoticeOptions {
+        max_scan_bytes: Some(1024),
+        ..Default::default()
+    };
+    check_file(
+        &dir.join("notice.js"),
+        &"TestHolder".into(),
+        "TestLicense",
+        &options,
+    )?;
+    let error = check_file(
+        &dir.join("minified.js"),
+        &"TestHolder".into(),
+        "TestLicense",
+        &options,
+    )
+    .unwrap_err();
+    assert_eq!(error.to_string(), "missing license notice line.");
+    Ok(())
+}
+
+#[test]
+fn check_and_fix_content() -> cu::Result<()> {
+    let options = NoticeOptions::default();
+    let content = "#!/bin/bash\r\necho hi\r\n";
+    let error = Format::Hash
+        .check_content(content, &"TestHolder".into(), "TestLicense", &options)
+        .unwrap_err();
+    assert_eq!(error.to_string(), "missing license notice line.");
+
+    let fixed = Format::Hash.fix_content(content, &"TestHolder".into(), "TestLicense", &options)?;
+    assert!(fixed.starts_with("#!/bin/bash\r\n# SPDX-License-Identifier: TestLicense\r\n"));
+    assert!(fixed.ends_with(" TestHolder\r\n\r\necho hi\r\n"));
+    Format::Hash.check_content(&fixed, &"TestHolder".into(), "TestLicense", &options)?;
+    Ok(())
+}
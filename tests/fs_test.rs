@@ -0,0 +1,106 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Pistonite
+
+use std::collections::BTreeMap;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use cu::pre::*;
+use lisensor::{Config, FileSystem, RunOptions, expand_braces, run_with_fs};
+
+/// File system with files in memory
+#[derive(Default)]
+struct MemoryFileSystem {
+    files: Mutex<BTreeMap<PathBuf, String>>,
+}
+
+impl MemoryFileSystem {
+    fn get(&self, path: &Path) -> cu::Result<String> {
+        let files = self.files.lock().unwrap();
+        let content = cu::check!(files.get(path), "file not found: '{}'", path.display())?;
+        Ok(content.clone())
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn reader(&self, path: &Path) -> cu::Result<Box<dyn BufRead + Send>> {
+        Ok(Box::new(std::io::Cursor::new(self.get(path)?.into_bytes())))
+    }
+
+    fn read_string(&self, path: &Path) -> cu::Result<String> {
+        self.get(path)
+    }
+
+    fn write(&self, path: &Path, content: &str) -> cu::Result<()> {
+        let mut files = self.files.lock().unwrap();
+        files.insert(path.to_path_buf(), content.to_string());
+        Ok(())
+    }
+
+    fn glob(&self, pattern: &str) -> cu::Result<Vec<PathBuf>> {
+        // only supports "*.ext" for the test
+        let suffix = cu::check!(pattern.strip_prefix('*'), "unsupported pattern")?;
+        let files = self.files.lock().unwrap();
+        Ok(files
+            .keys()
+            .filter(|path| path.to_string_lossy().ends_with(suffix))
+            .cloned()
+            .collect())
+    }
+
+    fn file_size(&self, path: &Path) -> cu::Result<u64> {
+        Ok(self.get(path)?.len() as u64)
+    }
+}
+
+#[test]
+fn run_in_memory() -> cu::Result<()> {
+    cu::cli::level("qq");
+    let fs = Arc::new(MemoryFileSystem::default());
+    fs.write(Path::new("a.rs"), "fn a() {}\n")?;
+    fs.write(Path::new("b.py"), "print('b')\n")?;
+    let config = Config::new(
+        "TestHolder".to_string(),
+        "TestLicense".to_string(),
+        vec!["*.rs".to_string()],
+    );
+
+    let config2 = config.clone();
+    let fs2: Arc<dyn FileSystem> = fs.clone();
+    let result =
+        cu::co::run(async move { run_with_fs(config2, RunOptions::default(), fs2, None).await })?;
+    assert!(result.is_err());
+
+    let options = RunOptions {
+        fix: true,
+        ..Default::default()
+    };
+    let fs2: Arc<dyn FileSystem> = fs.clone();
+    let result = cu::co::run(async move { run_with_fs(config, options, fs2, None).await })?;
+    assert!(result.is_ok());
+
+    let a = fs.read_string(Path::new("a.rs"))?;
+    assert!(a.starts_with("// SPDX-License-Identifier: TestLicense\n"));
+    assert!(a.ends_with("\n\nfn a() {}\n"));
+    assert_eq!(fs.read_string(Path::new("b.py"))?, "print('b')\n");
+    Ok(())
+}
+
+#[test]
+fn expand_brace_alternations() {
+    assert_eq!(expand_braces("src/*.rs"), vec!["src/*.rs"]);
+    assert_eq!(
+        expand_braces("src/**/*.{rs,py}"),
+        vec!["src/**/*.rs", "src/**/*.py"]
+    );
+    assert_eq!(
+        expand_braces("{a,b}/{c,d}"),
+        vec!["a/c", "a/d", "b/c", "b/d"]
+    );
+    assert_eq!(expand_braces("x.{rs,{c,h}}"), vec!["x.rs", "x.c", "x.h"]);
+    assert_eq!(expand_braces("{a}.{b,}"), vec!["{a}.b", "{a}."]);
+    assert_eq!(expand_braces("\\{a,b\\}"), vec!["[{]a,b[}]"]);
+    assert_eq!(expand_braces("{a\\,b,c}"), vec!["a,b", "c"]);
+    assert_eq!(expand_braces("{a,b"), vec!["{a,b"]);
+}
@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Pistonite
+
+mod common;
+use common::*;
+
+use std::path::Path;
+use std::process::Command;
+
+use lisensor::{Config, RunOptions, run_with_options};
+
+fn git(dir: &Path, args: &[&str]) -> cu::Result<()> {
+    let status = Command::new("git")
+        .current_dir(dir)
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .status()?;
+    if !status.success() {
+        cu::bail!("git {} failed", args.join(" "));
+    }
+    Ok(())
+}
+
+#[test]
+fn no_fix_if_uncommitted() -> cu::Result<()> {
+    let dir = setup(
+        "no_fix_if_uncommitted",
+        &[
+            ("clean.rs", "fn clean() {}\n"),
+            ("dirty.rs", "fn dirty() {}\n"),
+        ],
+    )?;
+    git(&dir, &["init", "-q"])?;
+    git(&dir, &["add", "."])?;
+    git(&dir, &["commit", "-q", "-m", "initial"])?;
+    std::fs::write(dir.join("dirty.rs"), "fn dirty() { todo!() }\n")?;
+
+    // git is queried in the current directory
+    std::env::set_current_dir(&dir)?;
+    let config = test_config(&dir, "*.rs");
+    let options = RunOptions {
+        fix: true,
+        no_fix_if_uncommitted: true,
+        ..Default::default()
+    };
+    let result = cu::co::run(async move { run_with_options(config, options).await })?;
+    let failure = result.unwrap_err();
+    assert_eq!(failure.failures.len(), 1);
+    assert!(failure.failures[0].path.ends_with("dirty.rs"));
+    assert!(failure.failures[0].message.contains("dirty.rs"));
+
+    let clean = std::fs::read_to_string(dir.join("clean.rs"))?;
+    assert!(clean.starts_with("// SPDX-License-Identifier: TestLicense\n"));
+    let dirty = std::fs::read_to_string(dir.join("dirty.rs"))?;
+    assert_eq!(dirty, "fn dirty() { todo!() }\n");
+
+    // conflicting config is still detected for dirty files
+    let mut config = test_config(&dir, "*.rs");
+    config.absorb(Config::new(
+        "Other".to_string(),
+        "MIT".to_string(),
+        vec![glob(&dir, "dirty.*")],
+    ))?;
+    let options = RunOptions {
+        fix: true,
+        no_fix_if_uncommitted: true,
+        ..Default::default()
+    };
+    let result = cu::co::run(async move { run_with_options(config, options).await });
+    assert!(result.is_err());
+    Ok(())
+}
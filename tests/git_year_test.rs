@@ -0,0 +1,86 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Pistonite
+
+mod common;
+use common::*;
+
+use std::path::Path;
+use std::process::Command;
+
+use lisensor::{RunOptions, run_with_options};
+
+fn git(dir: &Path, args: &[&str]) -> cu::Result<()> {
+    let status = Command::new("git")
+        .current_dir(dir)
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .status()?;
+    if !status.success() {
+        cu::bail!("git {} failed", args.join(" "));
+    }
+    Ok(())
+}
+
+#[test]
+fn git_year() -> cu::Result<()> {
+    // outside a git repo, the current year is used
+    let no_repo_dir = setup("git_year_no_repo", &[("a.rs", "fn a() {}\n")])?;
+    // SAFETY: this is the only test in this binary
+    unsafe {
+        // stop git from finding the repo of this crate
+        std::env::set_var("GIT_CEILING_DIRECTORIES", env!("CARGO_TARGET_TMPDIR"));
+    }
+    std::env::set_current_dir(&no_repo_dir)?;
+    let config = test_config(&no_repo_dir, "*.rs");
+    let options = RunOptions {
+        fix: true,
+        git_year: true,
+        ..Default::default()
+    };
+    let result = cu::co::run(async move { run_with_options(config, options).await })?;
+    assert!(result.is_ok());
+    let a = std::fs::read_to_string(no_repo_dir.join("a.rs"))?;
+    assert!(a.starts_with("// SPDX-License-Identifier: TestLicense\n"));
+
+    let dir = setup(
+        "git_year",
+        &[
+            ("old.rs", "fn old() {}\n"),
+            ("new.rs", "fn new() {}\n"),
+            ("before.rs", "fn moved() {}\n"),
+        ],
+    )?;
+    git(&dir, &["init", "-q"])?;
+    git(&dir, &["add", "old.rs", "before.rs"])?;
+    git(
+        &dir,
+        &["commit", "-q", "-m", "old", "--date", "2021-06-01T00:00:00"],
+    )?;
+    git(&dir, &["mv", "before.rs", "moved.rs"])?;
+    git(
+        &dir,
+        &["commit", "-q", "-m", "mv", "--date", "2023-06-01T00:00:00"],
+    )?;
+
+    // git is queried in the current directory
+    std::env::set_current_dir(&dir)?;
+    let config = test_config(&dir, "*.rs");
+    let options = RunOptions {
+        fix: true,
+        git_year: true,
+        ..Default::default()
+    };
+    let result = cu::co::run(async move { run_with_options(config, options).await })?;
+    assert!(result.is_ok());
+
+    let old = std::fs::read_to_string(dir.join("old.rs"))?;
+    assert!(old.contains("// Copyright (c) 2021-"));
+    // renamed files use the year they were added with the original name
+    let moved = std::fs::read_to_string(dir.join("moved.rs"))?;
+    assert!(moved.contains("// Copyright (c) 2021-"));
+    // untracked files use the current year
+    let new = std::fs::read_to_string(dir.join("new.rs"))?;
+    assert!(!new.contains("// Copyright (c) 2021"));
+    assert!(new.starts_with("// SPDX-License-Identifier: TestLicense\n"));
+    Ok(())
+}
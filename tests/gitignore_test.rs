@@ -0,0 +1,49 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Pistonite
+
+mod common;
+use common::*;
+
+use std::process::Command;
+
+use lisensor::{RunOptions, run_with_options};
+
+#[test]
+fn respect_gitignore() -> cu::Result<()> {
+    let dir = setup(
+        "respect_gitignore",
+        &[
+            (".gitignore", ".venv/\ngen/*\n!gen/keep.rs\n"),
+            ("src/a.rs", "fn a() {}\n"),
+            (".venv/lib/x.rs", "fn x() {}\n"),
+            ("gen/out.rs", "fn out() {}\n"),
+            ("gen/keep.rs", "fn keep() {}\n"),
+        ],
+    )?;
+    let status = Command::new("git")
+        .current_dir(&dir)
+        .args(["init", "-q"])
+        .status()?;
+    assert!(status.success());
+
+    // git is queried in the current directory
+    std::env::set_current_dir(&dir)?;
+    let config = test_config(&dir, "**/*.rs");
+    let options = RunOptions {
+        fix: true,
+        respect_gitignore: true,
+        ..Default::default()
+    };
+    let result = cu::co::run(async move { run_with_options(config, options).await })?;
+    assert!(result.is_ok());
+
+    for file in ["src/a.rs", "gen/keep.rs"] {
+        let content = std::fs::read_to_string(dir.join(file))?;
+        assert!(content.starts_with("// SPDX-License-Identifier: TestLicense\n"));
+    }
+    let x = std::fs::read_to_string(dir.join(".venv/lib/x.rs"))?;
+    assert_eq!(x, "fn x() {}\n");
+    let out = std::fs::read_to_string(dir.join("gen/out.rs"))?;
+    assert_eq!(out, "fn out() {}\n");
+    Ok(())
+}
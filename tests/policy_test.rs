@@ -0,0 +1,86 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Pistonite
+
+#![cfg(feature = "policy")]
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+
+use lisensor::{Config, fetch_policy};
+
+const POLICY: &str = r#"
+["Foobar"]
+"src/**/*.rs" = "MIT"
+"#;
+
+/// Serve `body` once over HTTP on a local port, and return the URL
+fn serve_once(body: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut buf = [0u8; 4096];
+        let _ = stream.read(&mut buf);
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        let _ = stream.write_all(response.as_bytes());
+    });
+    format!("http://{addr}/policy.toml")
+}
+
+/// Get a URL that nothing listens on, to simulate being offline
+fn unreachable_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    format!("http://{addr}/policy.toml")
+}
+
+fn fetch(url: String, cache_path: PathBuf) -> Option<Config> {
+    cu::co::run(async move { fetch_policy(&url, Path::new(""), &cache_path).await })
+}
+
+#[test]
+fn policy_fetch_and_fallback_to_cache() -> cu::Result<()> {
+    cu::cli::level("qq");
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("policy_fetch");
+    std::fs::create_dir_all(&dir)?;
+    let cache_path = dir.join("policy.toml");
+    if cache_path.exists() {
+        std::fs::remove_file(&cache_path)?;
+    }
+    let expected = Config::new(
+        "Foobar".to_string(),
+        "MIT".to_string(),
+        vec!["src/**/*.rs".to_string()],
+    );
+
+    // nothing available
+    assert_eq!(fetch(unreachable_url(), cache_path.clone()), None);
+
+    // fetched from server
+    let config = fetch(serve_once(POLICY), cache_path.clone());
+    assert_eq!(config.as_ref(), Some(&expected));
+    assert!(cache_path.exists());
+
+    // offline, using cache
+    let config = fetch(unreachable_url(), cache_path);
+    assert_eq!(config, Some(expected));
+    Ok(())
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn policy_remove_and_fix() -> cu::Result<()> {
+    use cu::pre::clap::Parser as _;
+    use lisensor::{Cli, config_from_policy};
+
+    let mut cli = Cli::try_parse_from(["lisensor", "--remove", "--fix"])?;
+    let url = unreachable_url();
+    let result = cu::co::run(async move { config_from_policy(&url, &mut cli).await });
+    assert!(result.is_err());
+    Ok(())
+}
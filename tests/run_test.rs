@@ -0,0 +1,647 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Pistonite
+
+mod common;
+use common::*;
+
+use std::path::Path;
+
+use lisensor::{
+    Config, IssueKind, OutputFormat, RunEvent, RunOptions, run, run_with, run_with_options,
+    run_with_output,
+};
+
+#[test]
+fn fix_only_path() -> cu::Result<()> {
+    let dir = setup(
+        "fix_only_path",
+        &[("core/a.rs", "fn a() {}\n"), ("other/b.rs", "fn b() {}\n")],
+    )?;
+    let config = test_config(&dir, "**/*.rs");
+    let options = RunOptions {
+        fix: true,
+        only_path: Some(glob(&dir, "core/**/*")),
+        ..Default::default()
+    };
+    let result = cu::co::run(async move { run_with_options(config, options).await })?;
+    // b.rs is only checked, so the run still has issues
+    assert!(result.is_err());
+
+    let a = std::fs::read_to_string(dir.join("core/a.rs"))?;
+    assert!(a.starts_with("// SPDX-License-Identifier: TestLicense\n"));
+    let b = std::fs::read_to_string(dir.join("other/b.rs"))?;
+    assert_eq!(b, "fn b() {}\n");
+    Ok(())
+}
+
+#[test]
+fn memory_budget_smaller_than_files() -> cu::Result<()> {
+    let content = "fn main() {}\n".repeat(1000);
+    let content = content.as_str();
+    let dir = setup(
+        "memory_budget_smaller_than_files",
+        &[("a.rs", content), ("b.rs", content), ("c.rs", content)],
+    )?;
+    let config = test_config(&dir, "*.rs");
+    let options = RunOptions {
+        fix: true,
+        memory_budget: Some(1024),
+        ..Default::default()
+    };
+    let result = cu::co::run(async move { run_with_options(config, options).await })?;
+    assert!(result.is_ok());
+    for file in ["a.rs", "b.rs", "c.rs"] {
+        let content = std::fs::read_to_string(dir.join(file))?;
+        assert!(content.starts_with("// SPDX-License-Identifier: TestLicense\n"));
+    }
+    Ok(())
+}
+
+#[test]
+fn single_job() -> cu::Result<()> {
+    let files = (0..20)
+        .map(|i| (format!("{i}.rs"), format!("fn f{i}() {{}}\n")))
+        .collect::<Vec<_>>();
+    let files = files
+        .iter()
+        .map(|(name, content)| (name.as_str(), content.as_str()))
+        .collect::<Vec<_>>();
+    let dir = setup("single_job", &files)?;
+    let config = test_config(&dir, "*.rs");
+    let options = RunOptions {
+        fix: true,
+        jobs: Some(1),
+        ..Default::default()
+    };
+    let result = cu::co::run(async move { run_with_options(config, options).await })?;
+    assert!(result.is_ok());
+    for (name, content) in files {
+        let fixed = std::fs::read_to_string(dir.join(name))?;
+        assert!(fixed.starts_with("// SPDX-License-Identifier: TestLicense\n"));
+        assert!(fixed.ends_with(&format!("\n\n{content}")));
+    }
+
+    let config = test_config(&dir, "*.rs");
+    let options = RunOptions {
+        jobs: Some(0),
+        ..Default::default()
+    };
+    assert!(cu::co::run(async move { run_with_options(config, options).await }).is_err());
+    Ok(())
+}
+
+#[test]
+fn diff_wrong_license() -> cu::Result<()> {
+    let content =
+        "// SPDX-License-Identifier: MIT\n// Copyright (c) 2026 TestHolder\n\nfn a() {}\n";
+    let correct = "// SPDX-License-Identifier: TestLicense\n// Copyright (c) 2026 TestHolder\n";
+    let dir = setup(
+        "diff_wrong_license",
+        &[("a.rs", content), ("b.rs", correct)],
+    )?;
+    let config = test_config(&dir, "*.rs");
+    let options = RunOptions {
+        diff: true,
+        ..Default::default()
+    };
+    let (result, out) = cu::co::run(async move {
+        let mut out = Vec::new();
+        let result = run_with_output(config, options, Some(&mut out)).await;
+        (result, out)
+    });
+    assert!(result?.is_err());
+    let path = dir.join("a.rs").display().to_string();
+    let expected = format!(
+        "--- a/{path}
++++ b/{path}
+@@ -1,4 +1,4 @@
+-// SPDX-License-Identifier: MIT
++// SPDX-License-Identifier: TestLicense
+ // Copyright (c) 2026 TestHolder
+ 
+ fn a() {{}}
+checked 2 files, found 1 issue(s).
+run with --fix to fix them automatically.
+"
+    );
+    assert_eq!(String::from_utf8(out)?, expected);
+    // nothing is modified
+    assert_eq!(std::fs::read_to_string(dir.join("a.rs"))?, content);
+    Ok(())
+}
+
+#[test]
+fn list_files() -> cu::Result<()> {
+    let dir = setup(
+        "list_files",
+        &[("b.rs", "fn b() {}\n"), ("a.rs", "fn a() {}\n")],
+    )?;
+    let mut config = test_config(&dir, "*.rs");
+    config.absorb(Config::new(
+        "Other".to_string(),
+        "MIT".to_string(),
+        vec![glob(&dir, "*.py")],
+    ))?;
+    let options = RunOptions {
+        fix: true,
+        list: true,
+        ..Default::default()
+    };
+    let (result, out) = cu::co::run(async move {
+        let mut out = Vec::new();
+        let result = run_with_output(config, options, Some(&mut out)).await;
+        (result, out)
+    });
+    assert!(result?.is_ok());
+    let expected = format!(
+        "{} (holder 'Other', license 'MIT'):\n  (no files)\n{} (holder 'TestHolder', license 'TestLicense'):\n  {}\n  {}\n",
+        glob(&dir, "*.py"),
+        glob(&dir, "*.rs"),
+        dir.join("a.rs").display(),
+        dir.join("b.rs").display(),
+    );
+    assert_eq!(String::from_utf8(out)?, expected);
+    // nothing is modified, even with --fix
+    assert_eq!(std::fs::read_to_string(dir.join("a.rs"))?, "fn a() {}\n");
+    Ok(())
+}
+
+#[test]
+fn summary_to_writer() -> cu::Result<()> {
+    let dir = setup(
+        "summary_to_writer",
+        &[("a.rs", "fn a() {}\n"), ("b.rs", "fn b() {}\n")],
+    )?;
+    let config = test_config(&dir, "*.rs");
+    let (result, out) = cu::co::run(async move {
+        let mut out = Vec::new();
+        let result = run_with_output(config, RunOptions::default(), Some(&mut out)).await;
+        (result, out)
+    });
+    assert!(result?.is_err());
+    assert_eq!(
+        String::from_utf8(out)?,
+        "checked 2 files, found 2 issue(s).\nrun with --fix to fix them automatically.\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn summary_per_holder() -> cu::Result<()> {
+    let dir = setup(
+        "summary_per_holder",
+        &[
+            ("a/1.rs", "fn a1() {}\n"),
+            (
+                "a/2.rs",
+                "// SPDX-License-Identifier: TestLicense\n// Copyright (c) 2026 HolderA\n",
+            ),
+            (
+                "b/1.rs",
+                "// SPDX-License-Identifier: TestLicense\n// Copyright (c) 2026 HolderB\n",
+            ),
+        ],
+    )?;
+    let mut config = Config::new(
+        "HolderA".to_string(),
+        "TestLicense".to_string(),
+        vec![glob(&dir, "a/*.rs")],
+    );
+    config.absorb(Config::new(
+        "HolderB".to_string(),
+        "TestLicense".to_string(),
+        vec![glob(&dir, "b/*.rs")],
+    ))?;
+    let options = RunOptions {
+        fix: true,
+        ..Default::default()
+    };
+    let (result, out) = cu::co::run(async move {
+        let mut out = Vec::new();
+        let result = run_with_output(config, options, Some(&mut out)).await;
+        (result, out)
+    });
+    assert!(result?.is_ok());
+    assert_eq!(
+        String::from_utf8(out)?,
+        "license check successful for 3 files.\n'HolderA': 1 files ok, 1 fixed; 'HolderB': 1 files ok\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn summary_lists_first_failures() -> cu::Result<()> {
+    let dir = setup(
+        "summary_lists_first_failures",
+        &[
+            ("1.rs", "fn a() {}\n"),
+            ("2.rs", "fn b() {}\n"),
+            (
+                "3.rs",
+                "// SPDX-License-Identifier: TestLicense\n// Copyright (c) 2026 TestHolder\n",
+            ),
+        ],
+    )?;
+    let config = test_config(&dir, "*.rs");
+    let options = RunOptions {
+        summary: Some(1),
+        ..Default::default()
+    };
+    let (result, out) = cu::co::run(async move {
+        let mut out = Vec::new();
+        let result = run_with_output(config, options, Some(&mut out)).await;
+        (result, out)
+    });
+    // all the files are still in the failure
+    assert_eq!(result?.unwrap_err().failures.len(), 2);
+    let out = String::from_utf8(out)?;
+    let mut lines = out.lines();
+    assert!(lines.next().is_some_and(|x| x.ends_with("1.rs")), "{out}");
+    assert_eq!(lines.next(), Some("checked 3 files, found 2 issue(s)."));
+    Ok(())
+}
+
+#[test]
+fn fail_fast() -> cu::Result<()> {
+    let dir = setup(
+        "fail_fast",
+        &[
+            ("a.rs", "fn a() {}\n"),
+            ("b.rs", "fn b() {}\n"),
+            ("c.rs", "fn c() {}\n"),
+        ],
+    )?;
+    let config = test_config(&dir, "*.rs");
+    let options = RunOptions {
+        fail_fast: true,
+        ..Default::default()
+    };
+    let result = cu::co::run(async move { run_with_options(config, options).await })?;
+    assert_eq!(result.unwrap_err().failures.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn config_from_paths() -> cu::Result<()> {
+    let dir = setup(
+        "config_from_paths",
+        &[
+            ("a[1].rs", "fn a() {}\n"),
+            (
+                "b.rs",
+                "// SPDX-License-Identifier: TestLicense\n// Copyright (c) 2026 TestHolder\n",
+            ),
+            ("c.rs", "fn c() {}\n"),
+        ],
+    )?;
+    let holder = "TestHolder".to_string();
+    let license = "TestLicense".to_string();
+    // the brackets are not treated as a glob character class
+    let paths = vec![dir.join("a[1].rs"), dir.join("b.rs")];
+    let config = Config::from_paths(holder.clone(), license.clone(), paths)?;
+    assert_eq!(config.clone().into_iter().count(), 2);
+    let result = cu::co::run(async move { run(config, false).await })?;
+    let failures = result.unwrap_err().failures;
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].path, dir.join("a[1].rs"));
+
+    let paths = vec![dir.join("b.rs"), dir.join("missing.rs")];
+    let config = Config::from_paths(holder, license, paths)?;
+    assert!(cu::co::run(async move { run(config, false).await }).is_err());
+    Ok(())
+}
+
+#[test]
+fn holder_from_glob_capture() -> cu::Result<()> {
+    let dir = setup(
+        "holder_from_glob_capture",
+        &[
+            ("packages/foo/src/lib.rs", "fn foo() {}\n"),
+            ("packages/bar/src/lib.rs", "fn bar() {}\n"),
+        ],
+    )?;
+    let config = Config::new(
+        "${1} Team".to_string(),
+        "TestLicense".to_string(),
+        vec![glob(&dir, "packages/*/src/**/*.rs")],
+    );
+    let options = RunOptions {
+        fix: true,
+        ..Default::default()
+    };
+    let result = cu::co::run(async move { run_with_options(config, options).await })?;
+    assert!(result.is_ok());
+    for package in ["foo", "bar"] {
+        let content = std::fs::read_to_string(dir.join(format!("packages/{package}/src/lib.rs")))?;
+        assert!(content.contains(&format!(" {package} Team\n")), "{content}");
+    }
+
+    // no capture for the template
+    let config = Config::new(
+        "${2} Team".to_string(),
+        "TestLicense".to_string(),
+        vec![glob(&dir, "packages/*/src/**/*.rs")],
+    );
+    let result = cu::co::run(async move { run_with_options(config, RunOptions::default()).await });
+    assert!(result.is_err());
+    Ok(())
+}
+
+#[test]
+fn fix_dry_run() -> cu::Result<()> {
+    let dir = setup(
+        "fix_dry_run",
+        &[
+            ("a.rs", "fn a() {}\n"),
+            ("b.rs", "fn b() {}\n"),
+            (
+                "c.rs",
+                "// SPDX-License-Identifier: TestLicense\n// Copyright (c) 2026 TestHolder\n",
+            ),
+        ],
+    )?;
+    let config = test_config(&dir, "*.rs");
+    let options = RunOptions {
+        fix: true,
+        dry_run: true,
+        ..Default::default()
+    };
+    let (result, out) = cu::co::run(async move {
+        let mut out = Vec::new();
+        let result = run_with_output(config, options, Some(&mut out)).await;
+        (result, out)
+    });
+    let failure = result?.unwrap_err();
+    assert_eq!(failure.failures.len(), 2);
+    assert!(failure.failures.iter().all(|x| x.fixable));
+    assert_eq!(
+        String::from_utf8(out)?,
+        "checked 3 files, found 2 issue(s).\n2 of them would be fixed, run without --dry-run to fix them.\n"
+    );
+    // nothing is modified
+    assert_eq!(std::fs::read_to_string(dir.join("a.rs"))?, "fn a() {}\n");
+    assert_eq!(std::fs::read_to_string(dir.join("b.rs"))?, "fn b() {}\n");
+    Ok(())
+}
+
+#[test]
+fn skip_binary_and_large_files() -> cu::Result<()> {
+    let notice = "// SPDX-License-Identifier: TestLicense\n// Copyright (c) 2026 TestHolder\n";
+    let large = format!("{notice}{}", "fn f() {}\n".repeat(1000));
+    let dir = setup(
+        "skip_binary_and_large_files",
+        &[
+            ("a.rs", notice),
+            ("b.rs", "fn b() {}\0\n"),
+            ("c.rs", &large),
+        ],
+    )?;
+    let config = test_config(&dir, "*.rs");
+    let options = RunOptions {
+        max_file_size: Some(1024),
+        ..Default::default()
+    };
+    let (result, out) = cu::co::run(async move {
+        let mut out = Vec::new();
+        let result = run_with_output(config, options, Some(&mut out)).await;
+        (result, out)
+    });
+    assert!(result?.is_ok());
+    assert_eq!(
+        String::from_utf8(out)?,
+        "license check successful for 1 files.\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn run_with_events() -> cu::Result<()> {
+    let dir = setup(
+        "run_with_events",
+        &[
+            ("a.rs", "fn a() {}\n"),
+            (
+                "b.rs",
+                "// SPDX-License-Identifier: TestLicense\n// Copyright (c) 2026 TestHolder\n",
+            ),
+        ],
+    )?;
+    let run = |fix: bool| -> cu::Result<Vec<String>> {
+        let config = test_config(&dir, "*.rs");
+        let options = RunOptions {
+            fix,
+            ..Default::default()
+        };
+        let (result, mut events) = cu::co::run(async move {
+            let mut events = Vec::new();
+            let result = run_with(config, options, |event| {
+                let (name, path) = match event {
+                    RunEvent::FileChecked { path, result } => {
+                        let kind = result.map_err(|issue| issue.kind);
+                        (format!("checked {kind:?}"), path)
+                    }
+                    RunEvent::FileFixed { path } => ("fixed".to_string(), path),
+                    RunEvent::FileSkipped { path } => ("skipped".to_string(), path),
+                };
+                let file = path.file_name().unwrap_or_default().to_string_lossy();
+                events.push(format!("{file} {name}"));
+            })
+            .await;
+            (result, events)
+        });
+        // the issues are checked with the events
+        let _ = result?;
+        events.sort();
+        Ok(events)
+    };
+    assert_eq!(
+        run(false)?,
+        vec!["a.rs checked Err(Missing)", "b.rs checked Ok(())"]
+    );
+    assert_eq!(
+        run(true)?,
+        vec!["a.rs checked Ok(())", "a.rs fixed", "b.rs checked Ok(())"]
+    );
+    Ok(())
+}
+
+#[test]
+fn brace_alternation_glob() -> cu::Result<()> {
+    let dir = setup(
+        "brace_alternation_glob",
+        &[
+            ("src/a.rs", "fn a() {}\n"),
+            ("src/sub/b.py", "print('b')\n"),
+            ("src/c.txt", "c\n"),
+        ],
+    )?;
+    let config = test_config(&dir, "src/**/*.{rs,py}");
+    let result = cu::co::run(async move { run_with_options(config, RunOptions::default()).await })?;
+    let failure = result.unwrap_err();
+    let paths = failure
+        .failures
+        .iter()
+        .map(|x| x.path.strip_prefix(&dir).unwrap_or(&x.path).to_path_buf())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        paths,
+        vec![Path::new("src/a.rs"), Path::new("src/sub/b.py")]
+    );
+    Ok(())
+}
+
+#[test]
+fn json_output() -> cu::Result<()> {
+    let dir = setup(
+        "json_output",
+        &[
+            ("a.rs", "fn a() {}\n"),
+            (
+                "b.py",
+                "# SPDX-License-Identifier: MIT\n# Copyright (c) 2020 TestHolder\n",
+            ),
+            (
+                "c.rs",
+                "// SPDX-License-Identifier: TestLicense\n// Copyright (c) 2020 \"Other\"\n",
+            ),
+            // the format is detected from the shebang
+            ("d.txt", "#!/bin/sh\necho hi\n"),
+        ],
+    )?;
+    let config = test_config(&dir, "*.*");
+    let options = RunOptions {
+        output_format: OutputFormat::Json,
+        ..Default::default()
+    };
+    let (result, out) = cu::co::run(async move {
+        let mut out = Vec::new();
+        let result = run_with_output(config, options, Some(&mut out)).await;
+        (result, out)
+    });
+    let failure = result?.unwrap_err();
+    let kinds = failure.failures.iter().map(|x| x.kind).collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec![
+            IssueKind::Missing,
+            IssueKind::WrongLicense,
+            IssueKind::WrongHolder,
+            IssueKind::Missing,
+        ]
+    );
+    let out = serde_json::from_slice::<serde_json::Value>(&out)?;
+    let results = out.as_array().unwrap();
+    assert_eq!(results.len(), 4);
+    let field = |i: usize, key: &str| results[i][key].clone();
+    assert_eq!(field(0, "format"), "slash");
+    assert_eq!(field(0, "status"), "missing");
+    assert!(field(0, "expected").is_null());
+    assert!(field(0, "actual").is_null());
+    assert_eq!(field(0, "message"), "missing license notice line.");
+    assert_eq!(field(1, "format"), "hash");
+    assert_eq!(field(1, "status"), "wrong-license");
+    assert_eq!(field(1, "expected"), "TestLicense");
+    assert_eq!(field(1, "actual"), "MIT");
+    assert_eq!(field(2, "status"), "wrong-holder");
+    assert_eq!(field(2, "expected"), "TestHolder");
+    // quotes in the values are escaped
+    assert_eq!(field(2, "actual"), "\"Other\"");
+    assert_eq!(field(3, "format"), "hash");
+    assert_eq!(field(3, "status"), "missing");
+    Ok(())
+}
+
+#[test]
+fn max_changes_exceeded() -> cu::Result<()> {
+    let files = [
+        ("a.rs", "fn a() {}\n"),
+        ("b.rs", "fn b() {}\n"),
+        ("c.rs", "fn c() {}\n"),
+    ];
+    let dir = setup("max_changes_exceeded", &files)?;
+    let config = test_config(&dir, "*.rs");
+    let options = RunOptions {
+        fix: true,
+        max_changes: Some(2),
+        ..Default::default()
+    };
+    let result = cu::co::run(async move { run_with_options(config, options).await });
+    assert!(result.is_err());
+    for (file, content) in files {
+        assert_eq!(std::fs::read_to_string(dir.join(file))?, content);
+    }
+    Ok(())
+}
+
+#[test]
+fn max_changes_not_exceeded() -> cu::Result<()> {
+    let dir = setup(
+        "max_changes_not_exceeded",
+        &[("a.rs", "fn a() {}\n"), ("b.rs", "fn b() {}\n")],
+    )?;
+    let config = test_config(&dir, "*.rs");
+    let options = RunOptions {
+        fix: true,
+        max_changes: Some(2),
+        ..Default::default()
+    };
+    let result = cu::co::run(async move { run_with_options(config, options).await })?;
+    assert!(result.is_ok());
+    let a = std::fs::read_to_string(dir.join("a.rs"))?;
+    assert!(a.starts_with("// SPDX-License-Identifier: TestLicense\n"));
+    Ok(())
+}
+
+#[test]
+fn year_only() -> cu::Result<()> {
+    let dir = setup(
+        "year_only",
+        &[
+            (
+                "a.rs",
+                "// SPDX-License-Identifier: TestLicense\n// Copyright (c) 2020 TestHolder\n",
+            ),
+            ("b.rs", "fn b() {}\n"),
+        ],
+    )?;
+    let config = test_config(&dir, "*.rs");
+    let options = RunOptions {
+        fix: true,
+        year_only: true,
+        ..Default::default()
+    };
+    let result = cu::co::run(async move { run_with_options(config, options).await })?;
+    // b.rs has a missing notice, which is not fixed
+    assert!(result.is_err());
+
+    let a = std::fs::read_to_string(dir.join("a.rs"))?;
+    assert!(a.starts_with("// SPDX-License-Identifier: TestLicense\n// Copyright (c) 2020-"));
+    let b = std::fs::read_to_string(dir.join("b.rs"))?;
+    assert_eq!(b, "fn b() {}\n");
+    Ok(())
+}
+
+#[test]
+fn exclude_skipped_in_fix() -> cu::Result<()> {
+    let dir = setup(
+        "exclude_skipped_in_fix",
+        &[
+            ("src/a.rs", "fn a() {}\n"),
+            ("src/generated/b.rs", "fn b() {}\n"),
+        ],
+    )?;
+    let config =
+        test_config(&dir, "src/**/*.rs").with_excludes(vec![glob(&dir, "src/generated/**")]);
+    let options = RunOptions {
+        fix: true,
+        ..Default::default()
+    };
+    let result = cu::co::run(async move { run_with_options(config, options).await })?;
+    assert!(result.is_ok());
+
+    let a = std::fs::read_to_string(dir.join("src/a.rs"))?;
+    assert!(a.starts_with("// SPDX-License-Identifier: TestLicense\n"));
+    let b = std::fs::read_to_string(dir.join("src/generated/b.rs"))?;
+    assert_eq!(b, "fn b() {}\n");
+    Ok(())
+}
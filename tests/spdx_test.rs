@@ -0,0 +1,28 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Pistonite
+
+#![cfg(feature = "spdx")]
+
+use lisensor::Config;
+
+fn config(license: &str) -> Config {
+    Config::new(
+        "TestHolder".to_string(),
+        license.to_string(),
+        vec!["*.rs".to_string()],
+    )
+}
+
+#[test]
+fn validate_licenses() {
+    cu::cli::level("qq");
+    assert!(config("MIT").validate().is_ok());
+    assert!(config("MIT OR Apache-2.0").validate().is_ok());
+    assert!(
+        config("GPL-2.0-only WITH Classpath-exception-2.0")
+            .validate()
+            .is_ok()
+    );
+    assert!(config("Apache2.0").validate().is_err());
+    assert!(config("MIT OR").validate().is_err());
+}
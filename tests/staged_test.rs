@@ -0,0 +1,85 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Pistonite
+
+mod common;
+use common::*;
+
+use std::path::Path;
+use std::process::Command;
+
+use lisensor::{RunOptions, run_with_options};
+
+fn git(dir: &Path, args: &[&str]) -> cu::Result<String> {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .output()?;
+    if !output.status.success() {
+        cu::bail!("git {} failed", args.join(" "));
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+#[test]
+fn staged_only() -> cu::Result<()> {
+    let dir = setup(
+        "staged_only",
+        &[
+            ("src/staged.rs", "fn staged() {}\n"),
+            ("src/unstaged.rs", "fn unstaged() {}\n"),
+            ("other.py", "print('hi')\n"),
+        ],
+    )?;
+    git(&dir, &["init", "-q"])?;
+    git(&dir, &["add", "src/staged.rs", "other.py"])?;
+
+    // git is queried in the current directory
+    std::env::set_current_dir(&dir)?;
+    let config = test_config(&dir, "src/*.rs");
+    let options = RunOptions {
+        staged: true,
+        ..Default::default()
+    };
+    let result = cu::co::run(async move { run_with_options(config, options).await })?;
+    let failure = result.unwrap_err();
+    // other.py is not matched by any glob, and unstaged.rs is not staged
+    assert_eq!(failure.failures.len(), 1);
+    assert!(failure.failures[0].path.ends_with("src/staged.rs"));
+
+    let config = test_config(&dir, "src/*.rs");
+    let options = RunOptions {
+        fix: true,
+        staged: true,
+        ..Default::default()
+    };
+    let result = cu::co::run(async move { run_with_options(config, options).await })?;
+    assert!(result.is_ok());
+    let staged = std::fs::read_to_string(dir.join("src/staged.rs"))?;
+    assert!(staged.starts_with("// SPDX-License-Identifier: TestLicense\n"));
+    let unstaged = std::fs::read_to_string(dir.join("src/unstaged.rs"))?;
+    assert_eq!(unstaged, "fn unstaged() {}\n");
+    // the fix is staged again
+    let diff = git(&dir, &["diff", "--name-only"])?;
+    assert!(diff.is_empty());
+
+    // partially staged files are not fixed, so the unstaged changes are not staged
+    std::fs::write(dir.join("src/partial.rs"), "fn partial() {}\n")?;
+    git(&dir, &["add", "src/partial.rs"])?;
+    std::fs::write(dir.join("src/partial.rs"), "fn partial() {}\nfn wip() {}\n")?;
+    let config = test_config(&dir, "src/*.rs");
+    let options = RunOptions {
+        fix: true,
+        staged: true,
+        ..Default::default()
+    };
+    let result = cu::co::run(async move { run_with_options(config, options).await })?;
+    let failure = result.unwrap_err();
+    assert_eq!(failure.failures.len(), 1);
+    assert!(failure.failures[0].path.ends_with("src/partial.rs"));
+    let partial = std::fs::read_to_string(dir.join("src/partial.rs"))?;
+    assert_eq!(partial, "fn partial() {}\nfn wip() {}\n");
+    let index = git(&dir, &["show", ":src/partial.rs"])?;
+    assert_eq!(index, "fn partial() {}\n");
+    Ok(())
+}
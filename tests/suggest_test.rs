@@ -0,0 +1,107 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Pistonite
+
+mod common;
+use common::*;
+
+use std::path::PathBuf;
+
+use lisensor::{
+    extend_config, suggest_config, suggest_globs, suggest_new_extension_globs, uncovered_files,
+    uncovered_files_with_extensions,
+};
+
+#[test]
+fn suggest_uncovered() -> cu::Result<()> {
+    let dir = setup(
+        "suggest_uncovered",
+        &[
+            ("build.rs", ""),
+            ("src/main.rs", ""),
+            ("src/a/b.rs", ""),
+            ("scripts/run.py", ""),
+            ("scripts/Makefile", ""),
+            ("assets/logo.png", ""),
+            (".github/workflow.yml", ""),
+            ("target/debug/out.rs", ""),
+        ],
+    )?;
+    let config = test_config(&dir, "src/**/*.rs");
+    let globs = suggest_globs(&dir, Some(&config))?;
+    let globs = globs.into_iter().collect::<Vec<_>>();
+    assert_eq!(
+        globs,
+        vec![
+            ("*.rs".to_string(), 1),
+            ("scripts/**/*.py".to_string(), 1),
+            ("scripts/**/Makefile".to_string(), 1),
+        ]
+    );
+
+    let globs = suggest_globs(&dir, None)?;
+    assert_eq!(globs.get("src/**/*.rs"), Some(&2));
+    let config = suggest_config(&globs, "Foo \"Bar\"", "MIT")?;
+    assert!(config.starts_with("[\"Foo \\\"Bar\\\"\"]\n"));
+    assert!(config.contains("\"src/**/*.rs\" = \"MIT\" # 2 file(s)\n"));
+    Ok(())
+}
+
+#[test]
+fn report_uncovered() -> cu::Result<()> {
+    let dir = setup(
+        "report_uncovered",
+        &[
+            ("src/main.rs", ""),
+            ("src/new/lib.rs", ""),
+            ("tools/gen.py", ""),
+            ("target/debug/build.rs", ""),
+        ],
+    )?;
+    let config = test_config(&dir, "src/*.rs");
+    let uncovered = uncovered_files(&dir, Some(&config))?;
+    assert_eq!(
+        uncovered,
+        vec![
+            PathBuf::from("src").join("new").join("lib.rs"),
+            PathBuf::from("tools").join("gen.py"),
+        ]
+    );
+
+    let extensions = ["rs".to_string()];
+    let uncovered = uncovered_files_with_extensions(&dir, Some(&config), &extensions)?;
+    assert_eq!(
+        uncovered,
+        vec![PathBuf::from("src").join("new").join("lib.rs")]
+    );
+    Ok(())
+}
+
+#[test]
+fn auto_extend_new_extension() -> cu::Result<()> {
+    let dir = setup(
+        "auto_extend_new_extension",
+        &[
+            ("src/main.rs", ""),
+            ("src/lib.rs", ""),
+            ("build.rs", ""),
+            ("scripts/gen.py", ""),
+            ("src/tools/check.py", ""),
+        ],
+    )?;
+    let config = test_config(&dir, "src/**/*.rs");
+    let (holder, license) = config.most_common_entry().unwrap();
+    // build.rs is not covered, but .rs files are already in the config
+    let globs = suggest_new_extension_globs(&dir, &config)?;
+    let globs_vec = globs.clone().into_iter().collect::<Vec<_>>();
+    assert_eq!(globs_vec, vec![("**/*.py".to_string(), 2)]);
+
+    let content = "[TestHolder]\n\"src/**/*.rs\" = \"TestLicense\"\n";
+    let extended = extend_config(content, &globs, holder, license)?;
+    assert_eq!(
+        extended,
+        "[TestHolder]\n\"**/*.py\" = \"TestLicense\" # 2 file(s)\n\"src/**/*.rs\" = \"TestLicense\"\n"
+    );
+    let extended = extend_config(content, &globs, "Other", license)?;
+    assert!(extended.ends_with("\n\n[\"Other\"]\n\"**/*.py\" = \"TestLicense\" # 2 file(s)\n"));
+    Ok(())
+}